@@ -0,0 +1,661 @@
+//! `#[actor]` attribute, the alternative to `actor!`.
+//!
+//! ```rust,ignore
+//! #[actor(tick_interval = 5)]
+//! pub struct CounterActor {
+//!     pub count: u32,
+//! }
+//!
+//! #[actor]
+//! impl CounterActor {
+//!     pub fn increment(&mut self, by: u32) {
+//!         self.count += by;
+//!     }
+//!     pub fn get(&self) -> u32 {
+//!         self.count
+//!     }
+//! }
+//! ```
+//!
+//! On the struct, it generates `start()` and `CounterActorHandle`, configured with the
+//! attribute's arguments. On the impl block, it generates `CounterActorInput` enum with a
+//! variant per method (`Increment(u32)`, `Get(movie::ReplyTo<u32>)`), implements
+//! `movie::Actor`, calling the methods, and generates `CounterActorHandleExt` with
+//! `increment(by)` and `get()` for the handle. The two halves only share names derived from the
+//! struct name. One of them is a hidden `macro_rules!` giving the generated items the
+//! struct's visibility, so the struct has to come before the impl block.
+
+use crate::input;
+use crate::parse::{self, is_punct, Error};
+use crate::template;
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+
+const STRUCT_ARGS: &[&str] = &[
+    "tick_interval",
+    "tick_schedule",
+    "mailbox_capacity",
+    "mailbox_overflow",
+    "spawner",
+    "spawner_return_type",
+    "stack_size",
+    "instance_id",
+];
+const IMPL_ARGS: &[&str] = &["input_derive"];
+const LIFECYCLE: &[&str] = &["on_init", "on_tick", "on_timer", "on_stop"];
+
+pub fn expand(args: TokenStream, item: TokenStream) -> Result<TokenStream, Error> {
+    let tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let keyword = tokens.iter().find_map(|token| match token {
+        TokenTree::Ident(ident) if ident.to_string() == "struct" => Some("struct"),
+        TokenTree::Ident(ident) if ident.to_string() == "impl" => Some("impl"),
+        _ => None,
+    });
+    match keyword {
+        Some("struct") => expand_struct(args, item, &tokens),
+        Some("impl") => expand_impl(args, item, &tokens),
+        _ => Err(Error::new(
+            Span::call_site(),
+            "`#[actor]` can only be used on a struct and its impl block",
+        )),
+    }
+}
+
+/// Parses `key = value, key(value)` arguments.
+fn parse_args(
+    args: TokenStream,
+    allowed: &[&'static str],
+) -> Result<HashMap<&'static str, TokenStream>, Error> {
+    let tokens: Vec<TokenTree> = args.into_iter().collect();
+    let mut parsed = HashMap::new();
+    for arg in input::split_commas(&tokens) {
+        if arg.is_empty() {
+            continue;
+        }
+        let key = match &arg[0] {
+            TokenTree::Ident(ident) => ident,
+            other => return Err(Error::new(other.span(), "expected argument name")),
+        };
+        let key_str = key.to_string();
+        let name = match allowed.iter().find(|name| **name == key_str) {
+            Some(name) => *name,
+            None => {
+                return Err(Error::new(
+                    key.span(),
+                    format!(
+                        "unknown argument `{}`, expected one of: {}",
+                        key_str,
+                        allowed.join(", ")
+                    ),
+                ))
+            }
+        };
+        let value: Vec<TokenTree> = match arg.get(1) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '=' && arg.len() > 2 => arg[2..].to_vec(),
+            Some(TokenTree::Group(group))
+                if group.delimiter() == Delimiter::Parenthesis && arg.len() == 2 =>
+            {
+                group.stream().into_iter().collect()
+            }
+            _ => {
+                return Err(Error::new(
+                    key.span(),
+                    format!("expected `{} = value`", key_str),
+                ))
+            }
+        };
+        // Checked like the `actor!` attribute of the same name
+        let value = parse::check_argument(&value, key)?;
+        if parsed.insert(name, value.into_iter().collect()).is_some() {
+            return Err(Error::new(
+                key.span(),
+                format!("duplicate argument `{}`", key_str),
+            ));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Returns the identifier after `keyword` (`struct Name` or `impl Name`), checking that
+/// it's not generic.
+fn name_after(tokens: &[TokenTree], keyword: &str) -> Result<(usize, Ident), Error> {
+    let position = tokens
+        .iter()
+        .position(|token| token.to_string() == keyword)
+        .unwrap();
+    let name = match tokens.get(position + 1) {
+        Some(TokenTree::Ident(ident)) => ident.clone(),
+        Some(other) => return Err(Error::new(other.span(), "expected name")),
+        None => return Err(Error::new(tokens[position].span(), "expected name")),
+    };
+    if let Some(token) = tokens.get(position + 2) {
+        if is_punct(token, '<') {
+            return Err(Error::new(token.span(), "generic actors are not supported"));
+        }
+    }
+    Ok((position, name))
+}
+
+fn suffixed(name: &Ident, suffix: &str) -> TokenStream {
+    TokenTree::from(Ident::new(&format!("{}{}", name, suffix), name.span())).into()
+}
+
+fn expand_struct(
+    args: TokenStream,
+    item: TokenStream,
+    tokens: &[TokenTree],
+) -> Result<TokenStream, Error> {
+    let mut args = parse_args(args, STRUCT_ARGS)?;
+    let (position, name) = name_after(tokens, "struct")?;
+    // Visibility is whatever is left after skipping attributes
+    let mut visibility = TokenStream::new();
+    let mut i = 0;
+    while i < position {
+        if is_punct(&tokens[i], '#') {
+            i += 2;
+        } else {
+            visibility.extend(Some(tokens[i].clone()));
+            i += 1;
+        }
+    }
+
+    let mut vars = HashMap::new();
+    vars.insert("item", item);
+    vars.insert("vis", visibility);
+    vars.insert("name", TokenTree::from(name.clone()).into());
+    vars.insert(
+        "name_str",
+        TokenTree::from(Literal::string(&name.to_string())).into(),
+    );
+    vars.insert("handle", suffixed(&name, "Handle"));
+    vars.insert("with_vis", suffixed(&name, "__movie_with_vis"));
+    vars.insert(
+        "mailbox",
+        crate::mailbox(
+            args.remove("mailbox_capacity"),
+            args.remove("mailbox_overflow"),
+        )?,
+    );
+    vars.insert(
+        "tick_interval",
+        args.remove("tick_interval")
+            .unwrap_or_else(|| "100".parse().unwrap()),
+    );
+    vars.insert(
+        "tick_schedule",
+        crate::tick_schedule(args.remove("tick_schedule"))?,
+    );
+    vars.insert(
+        "stack_size",
+        match args.remove("stack_size") {
+            Some(stack_size) => {
+                let mut vars = HashMap::new();
+                vars.insert("stack_size", stack_size);
+                template::expand("Some(#stack_size)", &vars)
+            }
+            None => "None".parse().unwrap(),
+        },
+    );
+    let thread_name = match args.remove("instance_id") {
+        Some(instance_id) => {
+            vars.insert("instance_id", instance_id);
+            template::expand("format!(\"{}-{}\", #name_str, #instance_id)", &vars)
+        }
+        None => template::expand("#name_str.to_string()", &vars),
+    };
+    vars.insert("thread_name", thread_name);
+    let start = match args.remove("spawner") {
+        Some(spawner) => {
+            vars.insert("spawner", spawner);
+            template::expand(
+                "
+                /// Spawns the actor, see `#[actor]`.
+                #vis fn start(self) -> #handle {
+                    movie::start_with(self, #spawner)
+                }
+                ",
+                &vars,
+            )
+        }
+        None => template::expand(
+            "
+            /// Spawns the actor in a new thread, see `movie::start()`.
+            #vis fn start(self) -> #handle {
+                movie::start(self)
+            }
+            /// Like `start()`, but returns the error if the thread can't be spawned.
+            #vis fn try_start(self) -> std::io::Result<#handle> {
+                movie::try_start(self)
+            }
+            ",
+            &vars,
+        ),
+    };
+    vars.insert("start", start);
+    let spawner_return_type = match args.remove("spawner_return_type") {
+        Some(spawner_return_type) => spawner_return_type,
+        None => template::expand(
+            "std::thread::JoinHandle<<#name as movie::Actor>::Output>",
+            &vars,
+        ),
+    };
+    vars.insert("spawner_return_type", spawner_return_type);
+
+    // The impl block's `movie::Actor` implementation uses the hidden items
+    Ok(template::expand(
+        "
+        #item
+
+        #vis type #handle = movie::Handle<#spawner_return_type, <#name as movie::Actor>::Input>;
+
+        // Used by the impl block, for items as visible as the struct
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #with_vis {
+            ($(#[$attr:meta])* enum $($item:tt)*) => {
+                $(#[$attr])* #vis enum $($item)*
+            };
+            ($(#[$attr:meta])* trait $($item:tt)*) => {
+                $(#[$attr])* #vis trait $($item)*
+            };
+        }
+
+        impl #name {
+            const __MOVIE_TICK_INTERVAL: std::time::Duration =
+                std::time::Duration::from_millis(#tick_interval);
+            const __MOVIE_TICK_SCHEDULE: movie::TickSchedule = #tick_schedule;
+            const __MOVIE_STACK_SIZE: Option<usize> = #stack_size;
+            fn __movie_mailbox<TX>() -> (movie::Addr<TX>, movie::Mailbox<TX>) {
+                #mailbox
+            }
+            fn __movie_thread_name(&self) -> String {
+                #thread_name
+            }
+            #start
+            /// Runs the actor on the current thread until it stops, see
+            /// `movie::run_here()`.
+            #vis fn run_here(self) -> <Self as movie::Actor>::Output {
+                movie::run_here(self)
+            }
+            /// Runs the actor on the current thread, after giving its handle to `f`, see
+            /// `movie::run_with_handle()`.
+            #vis fn run_with_handle(
+                self,
+                f: impl FnOnce(
+                    movie::Handle<
+                        movie::local::LocalJoinHandle<<Self as movie::Actor>::Output>,
+                        <Self as movie::Actor>::Input,
+                    >,
+                ),
+            ) {
+                movie::run_with_handle(self, f)
+            }
+        }
+        ",
+        &vars,
+    ))
+}
+
+/// Method of the `#[actor]` impl block.
+struct Method {
+    /// Doc comments and other attributes.
+    attrs: Vec<TokenTree>,
+    name: Ident,
+    /// Takes `self` by value, not by reference.
+    by_value: bool,
+    /// Takes `ctx` as the first argument after `self`.
+    takes_ctx: bool,
+    /// Types of the other arguments.
+    arg_types: Vec<TokenStream>,
+    returns: Option<TokenStream>,
+}
+
+fn expand_impl(
+    args: TokenStream,
+    item: TokenStream,
+    tokens: &[TokenTree],
+) -> Result<TokenStream, Error> {
+    let mut args = parse_args(args, IMPL_ARGS)?;
+    let (position, name) = name_after(tokens, "impl")?;
+    let body = match tokens.get(position + 2) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        Some(other) => {
+            return Err(Error::new(
+                other.span(),
+                "expected `{`, trait impls are not supported",
+            ))
+        }
+        None => return Err(Error::new(name.span(), "expected `{`")),
+    };
+    let methods = parse_methods(body)?;
+
+    let mut vars = HashMap::new();
+    vars.insert("item", item);
+    vars.insert("name", TokenTree::from(name.clone()).into());
+    vars.insert("input", suffixed(&name, "Input"));
+    vars.insert(
+        "name_str",
+        TokenTree::from(Literal::string(&name.to_string())).into(),
+    );
+    vars.insert(
+        "input_derive",
+        match args.remove("input_derive") {
+            Some(derives) => {
+                let mut vars = HashMap::new();
+                vars.insert("derives", derives);
+                template::expand("#[derive(#derives)]", &vars)
+            }
+            None => TokenStream::new(),
+        },
+    );
+
+    let handlers: Vec<&Method> = methods
+        .iter()
+        .filter(|method| !LIFECYCLE.contains(&method.name.to_string().as_str()))
+        .collect();
+    // Input is as visible as the struct
+    vars.insert("with_vis", suffixed(&name, "__movie_with_vis"));
+
+    let mut variants = TokenStream::new();
+    let mut arms = TokenStream::new();
+    for method in &handlers {
+        let variant = Ident::new(&camel_case(&method.name.to_string()), method.name.span());
+        let mut fields = TokenStream::new();
+        for arg_type in &method.arg_types {
+            fields.extend(arg_type.clone());
+            fields.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+        }
+        let args: Vec<String> = (0..method.arg_types.len())
+            .map(|i| format!("arg{}", i))
+            .collect();
+
+        // Declared like in `actor!`'s `input`, e.g. `/// docs GetState(u32) -> u64`
+        let mut declaration: TokenStream = method.attrs.iter().cloned().collect();
+        declaration.extend(Some(TokenTree::from(variant.clone())));
+        if !method.arg_types.is_empty() {
+            declaration.extend(Some(TokenTree::from(Group::new(
+                Delimiter::Parenthesis,
+                fields,
+            ))));
+        }
+        if let Some(returns) = &method.returns {
+            declaration.extend("->".parse::<TokenStream>().unwrap());
+            declaration.extend(returns.clone());
+        }
+        variants.extend(declaration);
+        variants.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+
+        let mut pattern_args = args.clone();
+        let mut call_args = args;
+        if method.takes_ctx {
+            call_args.insert(0, "ctx".to_string());
+        }
+        if method.returns.is_some() {
+            pattern_args.push("reply".to_string());
+        }
+        let pattern = if pattern_args.is_empty() {
+            String::new()
+        } else {
+            format!("({})", pattern_args.join(", "))
+        };
+        call_args.insert(0, "self".to_string());
+        // Called with a path, as `self.method()` could pick a method of `movie::Actor`
+        let call = format!("#name::#method({})", call_args.join(", "));
+        let arm = if method.returns.is_some() {
+            format!("#input::#variant{} => reply.send({}),", pattern, call)
+        } else {
+            format!("#input::#variant{} => {{ {}; }}", pattern, call)
+        };
+        let mut arm_vars = HashMap::new();
+        arm_vars.insert("input", vars["input"].clone());
+        arm_vars.insert("name", vars["name"].clone());
+        arm_vars.insert("variant", TokenTree::from(variant).into());
+        arm_vars.insert("method", TokenTree::from(method.name.clone()).into());
+        arms.extend(template::expand(&arm, &arm_vars));
+    }
+    let variants = input::parse(variants)?;
+    vars.insert("variants", input::expand(&variants));
+    let (declarations, definitions) =
+        input::expand_methods(&variants, &vars["input"], &vars["input"])?;
+    vars.insert("method_declarations", declarations);
+    vars.insert("method_definitions", definitions);
+    vars.insert("handle_ext", suffixed(&name, "HandleExt"));
+    vars.insert("arms", arms);
+
+    // Lifecycle methods are optional, `movie::Actor` has defaults for them
+    let find = |lifecycle: &str| {
+        methods
+            .iter()
+            .find(|method| method.name.to_string() == lifecycle)
+    };
+    let mut lifecycle = TokenStream::new();
+    for hook in &["on_init", "on_tick"] {
+        if let Some(method) = find(hook) {
+            let ctx = if method.takes_ctx { ", ctx" } else { "" };
+            let mut hook_vars = HashMap::new();
+            hook_vars.insert("name", vars["name"].clone());
+            hook_vars.insert("input", vars["input"].clone());
+            hook_vars.insert("method", TokenTree::from(method.name.clone()).into());
+            lifecycle.extend(template::expand(
+                &format!(
+                    "fn #method(&mut self, ctx: &mut movie::Context<#input>) {{
+                        #name::#method(self{});
+                    }}",
+                    ctx
+                ),
+                &hook_vars,
+            ));
+        }
+    }
+    if let Some(method) = find("on_timer") {
+        let ctx = if method.takes_ctx { "ctx, " } else { "" };
+        let mut hook_vars = HashMap::new();
+        hook_vars.insert("name", vars["name"].clone());
+        hook_vars.insert("input", vars["input"].clone());
+        hook_vars.insert("method", TokenTree::from(method.name.clone()).into());
+        lifecycle.extend(template::expand(
+            &format!(
+                "fn #method(&mut self, ctx: &mut movie::Context<#input>, timer: &'static str) {{
+                    #name::#method(self, {}timer);
+                }}",
+                ctx
+            ),
+            &hook_vars,
+        ));
+    }
+    vars.insert("lifecycle", lifecycle);
+    let (on_stop, returns) = match find("on_stop") {
+        Some(method) => {
+            let receiver = if method.by_value { "self" } else { "&mut self" };
+            let ctx = if method.takes_ctx { ", ctx" } else { "" };
+            let mut call_vars = HashMap::new();
+            call_vars.insert("name", vars["name"].clone());
+            call_vars.insert("method", TokenTree::from(method.name.clone()).into());
+            (
+                template::expand(&format!("#name::#method({}{})", receiver, ctx), &call_vars),
+                method
+                    .returns
+                    .clone()
+                    .unwrap_or_else(|| "()".parse().unwrap()),
+            )
+        }
+        None => (TokenStream::new(), "()".parse().unwrap()),
+    };
+    vars.insert("on_stop", on_stop);
+    vars.insert("returns", returns);
+
+    Ok(template::expand(
+        "
+        #item
+
+        #with_vis! {
+            #input_derive
+            enum #input {
+                #variants
+            }
+        }
+
+        #with_vis! {
+            /// Methods sending each variant of the input, named like the handlers.
+            /// Implemented for `movie::Addr`, and so usable on the handle.
+            #[allow(dead_code)]
+            trait #handle_ext {
+                #method_declarations
+            }
+        }
+
+        impl #handle_ext for movie::Addr<#input> {
+            #method_definitions
+        }
+
+        #[allow(unused_variables, unused_mut)]
+        impl movie::Actor for #name {
+            type Input = #input;
+            type Output = #returns;
+            const TICK_INTERVAL: std::time::Duration = Self::__MOVIE_TICK_INTERVAL;
+            const TICK_SCHEDULE: movie::TickSchedule = Self::__MOVIE_TICK_SCHEDULE;
+            const STACK_SIZE: Option<usize> = Self::__MOVIE_STACK_SIZE;
+            fn name() -> &'static str {
+                #name_str
+            }
+            fn thread_name(&self) -> String {
+                Self::__movie_thread_name(self)
+            }
+            fn mailbox() -> (movie::Addr<#input>, movie::Mailbox<#input>) {
+                Self::__movie_mailbox()
+            }
+            #lifecycle
+            fn on_message(&mut self, ctx: &mut movie::Context<#input>, message: #input) {
+                match message {
+                    #arms
+                }
+            }
+            fn on_stop(mut self, ctx: &mut movie::Context<#input>) -> #returns {
+                #on_stop
+            }
+        }
+        ",
+        &vars,
+    ))
+}
+
+/// Finds methods taking `self` in the impl block.
+fn parse_methods(body: TokenStream) -> Result<Vec<Method>, Error> {
+    let tokens: Vec<TokenTree> = body.into_iter().collect();
+    let mut methods = Vec::new();
+    let mut start = 0;
+    for i in 0..tokens.len() {
+        // Items end with `;` or a block
+        let end = match &tokens[i] {
+            TokenTree::Punct(p) => p.as_char() == ';',
+            TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+            _ => false,
+        };
+        if end {
+            if let Some(method) = parse_method(&tokens[start..=i])? {
+                methods.push(method);
+            }
+            start = i + 1;
+        }
+    }
+    Ok(methods)
+}
+
+fn parse_method(tokens: &[TokenTree]) -> Result<Option<Method>, Error> {
+    let fn_index = match tokens.iter().position(|token| token.to_string() == "fn") {
+        Some(fn_index) => fn_index,
+        None => return Ok(None),
+    };
+    let mut attrs = Vec::new();
+    let mut i = 0;
+    while i < fn_index && is_punct(&tokens[i], '#') {
+        attrs.extend(tokens[i..i + 2].iter().cloned());
+        i += 2;
+    }
+    let name = match tokens.get(fn_index + 1) {
+        Some(TokenTree::Ident(ident)) => ident.clone(),
+        _ => return Ok(None),
+    };
+    let params = match tokens.get(fn_index + 2) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => group,
+        Some(other) if is_punct(other, '<') => {
+            return Err(Error::new(
+                other.span(),
+                "generic methods can't be actor handlers",
+            ))
+        }
+        _ => return Ok(None),
+    };
+    let params: Vec<TokenTree> = params.stream().into_iter().collect();
+    let mut params = input::split_commas(&params)
+        .into_iter()
+        .filter(|param| !param.is_empty());
+    let by_value = match params.next() {
+        Some(receiver) if receiver.iter().any(|token| token.to_string() == "self") => {
+            !is_punct(&receiver[0], '&')
+        }
+        // Associated functions are left alone
+        _ => return Ok(None),
+    };
+    if by_value && name.to_string() != "on_stop" {
+        return Err(Error::new(
+            name.span(),
+            "actor methods must take `&self` or `&mut self` (only `on_stop` can take `self`)",
+        ));
+    }
+
+    let mut takes_ctx = false;
+    let mut arg_types = Vec::new();
+    for (n, param) in params.enumerate() {
+        let colon = (0..param.len()).find(|&i| {
+            is_punct(&param[i], ':')
+                && !(i > 0 && is_punct(&param[i - 1], ':'))
+                && !param.get(i + 1).is_some_and(|next| is_punct(next, ':'))
+        });
+        let colon = match colon {
+            Some(colon) => colon,
+            None => return Err(Error::new(param[0].span(), "expected `name: Type`")),
+        };
+        if n == 0 && colon == 1 && param[0].to_string() == "ctx" {
+            takes_ctx = true;
+            continue;
+        }
+        arg_types.push(param[colon + 1..].iter().cloned().collect());
+    }
+
+    // `-> Type` up to the body or `where`
+    let header_end = tokens.len() - 1;
+    let returns = (fn_index + 3..header_end)
+        .find(|&i| is_punct(&tokens[i], '-') && is_punct(&tokens[i + 1], '>'))
+        .map(|arrow| {
+            tokens[arrow + 2..header_end]
+                .iter()
+                .take_while(|token| token.to_string() != "where")
+                .cloned()
+                .collect::<TokenStream>()
+        })
+        .filter(|returns| returns.to_string() != "()");
+
+    Ok(Some(Method {
+        attrs,
+        name,
+        by_value,
+        takes_ctx,
+        arg_types,
+        returns,
+    }))
+}
+
+/// `change_source` -> `ChangeSource`
+fn camel_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
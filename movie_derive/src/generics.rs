@@ -0,0 +1,246 @@
+//! Generic parameters and where clause of an actor, declared after its name, e.g.
+//! `SinkActor<'a, S: Sink + 'a> where S: Clone`.
+//!
+//! They are applied to `Actor`, `Input` and `Handle`. Parameters are written in a
+//! different form in each place: with bounds and defaults in declarations, without
+//! defaults in impls and with only names in arguments.
+
+use crate::input::split_commas;
+use crate::parse::{is_punct, Error};
+use crate::template;
+use proc_macro::{Punct, Spacing, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct Generics {
+    params: Vec<Param>,
+    /// Predicates after `where`.
+    predicates: Vec<TokenTree>,
+}
+
+struct Param {
+    /// `'a` or `T`.
+    name: Vec<TokenTree>,
+    lifetime: bool,
+    /// Bounds after the colon.
+    bounds: Vec<TokenTree>,
+    /// Default type after `=`.
+    default: Vec<TokenTree>,
+}
+
+impl Generics {
+    /// Parses `<...> where ...`. Both parts are optional.
+    pub fn parse(tokens: &[TokenTree]) -> Result<Generics, Error> {
+        let mut generics = Generics::default();
+        let mut rest = tokens;
+        if rest.first().is_some_and(|token| is_punct(token, '<')) {
+            let end = closing_angle(rest)?;
+            for param in split_commas(&rest[1..end]) {
+                if !param.is_empty() {
+                    generics.params.push(parse_param(param)?);
+                }
+            }
+            rest = &rest[end + 1..];
+        }
+        match rest.first() {
+            None => (),
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "where" => {
+                generics.predicates = rest[1..].to_vec();
+                if generics.predicates.last().is_some_and(|t| is_punct(t, ',')) {
+                    generics.predicates.pop();
+                }
+            }
+            Some(other) => {
+                return Err(Error::new(
+                    other.span(),
+                    "expected attribute, e.g. `input:`",
+                ))
+            }
+        }
+        Ok(generics)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.predicates.is_empty()
+    }
+
+    /// `<'a, T: Bound = Default>`, for struct and enum declarations.
+    pub fn declaration(&self) -> TokenStream {
+        self.angled(|param, tokens| {
+            param.with_bounds(tokens);
+            param.with_default(tokens);
+        })
+    }
+
+    /// `<'a, T: Bound>`, for `impl<...>`.
+    pub fn impl_params(&self) -> TokenStream {
+        self.angled(|param, tokens| param.with_bounds(tokens))
+    }
+
+    /// `<'a, T = Default>`, for type aliases, which can't have bounds.
+    pub fn alias_params(&self) -> TokenStream {
+        self.angled(|param, tokens| param.with_default(tokens))
+    }
+
+    /// `<'a, T>`, for using the generated types.
+    pub fn args(&self) -> TokenStream {
+        self.angled(|_, _| ())
+    }
+
+    /// `where ...`, or nothing.
+    pub fn where_clause(&self) -> TokenStream {
+        if self.predicates.is_empty() {
+            return TokenStream::new();
+        }
+        let mut tokens: TokenStream = "where".parse().unwrap();
+        tokens.extend(self.predicates.iter().cloned());
+        tokens
+    }
+
+    /// Variant that uses all parameters, so that `Input` compiles even if its other
+    /// variants don't. It holds `Infallible`, so it can't be created. Empty if there are
+    /// no parameters.
+    pub fn phantom_variant(&self) -> TokenStream {
+        if self.params.is_empty() {
+            return TokenStream::new();
+        }
+        let mut types = TokenStream::new();
+        for param in &self.params {
+            if param.lifetime {
+                types.extend("&".parse::<TokenStream>().unwrap());
+                types.extend(param.name.iter().cloned());
+                types.extend("()".parse::<TokenStream>().unwrap());
+            } else {
+                types.extend(param.name.iter().cloned());
+            }
+            types.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+        }
+        let mut vars = HashMap::new();
+        vars.insert("types", types);
+        template::expand(
+            "#[doc(hidden)]
+            __Phantom(std::marker::PhantomData<fn() -> (#types)>, std::convert::Infallible),",
+            &vars,
+        )
+    }
+
+    /// Match arm for the phantom variant. Empty if there are no parameters.
+    pub fn phantom_arm(&self, input: &str) -> TokenStream {
+        if self.params.is_empty() {
+            return TokenStream::new();
+        }
+        format!(
+            "#[allow(unreachable_patterns)] {}::__Phantom(_, never) => match never {{}},",
+            input
+        )
+        .parse()
+        .unwrap()
+    }
+
+    fn angled(&self, mut param_tokens: impl FnMut(&Param, &mut Vec<TokenTree>)) -> TokenStream {
+        if self.params.is_empty() {
+            return TokenStream::new();
+        }
+        let mut tokens = vec![Punct::new('<', Spacing::Alone).into()];
+        for param in &self.params {
+            tokens.extend(param.name.iter().cloned());
+            param_tokens(param, &mut tokens);
+            tokens.push(Punct::new(',', Spacing::Alone).into());
+        }
+        tokens.push(Punct::new('>', Spacing::Alone).into());
+        tokens.into_iter().collect()
+    }
+}
+
+impl Param {
+    fn with_bounds(&self, tokens: &mut Vec<TokenTree>) {
+        if !self.bounds.is_empty() {
+            tokens.push(Punct::new(':', Spacing::Alone).into());
+            tokens.extend(self.bounds.iter().cloned());
+        }
+    }
+
+    fn with_default(&self, tokens: &mut Vec<TokenTree>) {
+        if !self.default.is_empty() {
+            tokens.push(Punct::new('=', Spacing::Alone).into());
+            tokens.extend(self.default.iter().cloned());
+        }
+    }
+}
+
+/// Index of `>` closing `<` at `tokens[0]`.
+fn closing_angle(tokens: &[TokenTree]) -> Result<usize, Error> {
+    let mut depth = 0;
+    for i in 0..tokens.len() {
+        match &tokens[i] {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            // `->` is not a closing bracket
+            TokenTree::Punct(p)
+                if p.as_char() == '>' && !(i > 0 && is_punct(&tokens[i - 1], '-')) =>
+            {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            _ => (),
+        }
+    }
+    Err(Error::new(tokens[0].span(), "unclosed `<`"))
+}
+
+fn parse_param(tokens: &[TokenTree]) -> Result<Param, Error> {
+    let (name, lifetime) = match tokens {
+        [TokenTree::Punct(quote), TokenTree::Ident(_), ..] if quote.as_char() == '\'' => {
+            (tokens[..2].to_vec(), true)
+        }
+        [TokenTree::Ident(ident), ..] if ident.to_string() == "const" => {
+            return Err(Error::new(ident.span(), "const generics are not supported"))
+        }
+        [TokenTree::Ident(_), ..] => (tokens[..1].to_vec(), false),
+        [other, ..] => return Err(Error::new(other.span(), "expected generic parameter")),
+        [] => unreachable!(),
+    };
+    let mut rest = &tokens[name.len()..];
+    let mut bounds = Vec::new();
+    if rest.first().is_some_and(|token| is_punct(token, ':')) {
+        // `=` of the default, not of `Iterator<Item = T>`
+        let mut depth = 0;
+        let end = (0..rest.len())
+            .find(|&i| match &rest[i] {
+                TokenTree::Punct(p) if p.as_char() == '<' => {
+                    depth += 1;
+                    false
+                }
+                TokenTree::Punct(p) if p.as_char() == '>' && !is_punct(&rest[i - 1], '-') => {
+                    depth -= 1;
+                    false
+                }
+                TokenTree::Punct(p) => p.as_char() == '=' && depth == 0,
+                _ => false,
+            })
+            .unwrap_or(rest.len());
+        bounds = rest[1..end].to_vec();
+        if bounds.is_empty() {
+            return Err(Error::new(rest[0].span(), "expected bounds after `:`"));
+        }
+        rest = &rest[end..];
+    }
+    let mut default = Vec::new();
+    if let Some(first) = rest.first() {
+        if !is_punct(first, '=') || lifetime {
+            return Err(Error::new(first.span(), "unexpected token"));
+        }
+        default = rest[1..].to_vec();
+        if default.is_empty() {
+            return Err(Error::new(first.span(), "expected type after `=`"));
+        }
+    }
+    Ok(Param {
+        name,
+        lifetime,
+        bounds,
+        default,
+    })
+}
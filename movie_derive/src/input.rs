@@ -0,0 +1,353 @@
+//! Parsing and expansion of `input` attribute.
+//!
+//! Variants are written like in an enum, except that a variant may declare its reply
+//! type: `GetState -> u64` or `Add(u32, u32) -> u32`. Such variants get
+//! `movie::ReplyTo<Reply>` as their last field.
+//!
+//! Every variant also gets a method sending it, e.g. `get_state()`, declared in a trait
+//! implemented for `movie::Addr<Input>` (see [`expand_methods`]).
+//!
+//! [`expand_methods`]: fn.expand_methods.html
+
+use crate::parse::{is_punct, Error};
+use crate::template;
+use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+
+pub struct Variant {
+    /// Attributes, name and fields, as written by the user.
+    tokens: Vec<TokenTree>,
+    /// Type of the reply, for reply-bearing variants.
+    pub reply: Option<TokenStream>,
+}
+
+pub fn parse(input: TokenStream) -> Result<Vec<Variant>, Error> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    split_commas(&tokens)
+        .into_iter()
+        .filter(|variant| !variant.is_empty())
+        .map(parse_variant)
+        .collect()
+}
+
+fn parse_variant(tokens: &[TokenTree]) -> Result<Variant, Error> {
+    // Skip attributes (`#[...]`, including doc comments)
+    let mut i = 0;
+    while i < tokens.len() && is_punct(&tokens[i], '#') {
+        i += 2;
+    }
+    match tokens.get(i) {
+        Some(TokenTree::Ident(_)) => (),
+        Some(other) => return Err(Error::new(other.span(), "expected variant name")),
+        None => return Err(Error::new(tokens[0].span(), "expected variant name")),
+    }
+    let fields_end = match tokens.get(i + 1) {
+        Some(TokenTree::Group(group)) if group.delimiter() != Delimiter::Bracket => i + 2,
+        _ => i + 1,
+    };
+
+    let arrow = match (fields_end..tokens.len()).find(|&i| is_arrow(tokens, i)) {
+        Some(arrow) => arrow,
+        None => {
+            return Ok(Variant {
+                tokens: tokens.to_vec(),
+                reply: None,
+            })
+        }
+    };
+    if arrow != fields_end {
+        return Err(Error::new(
+            tokens[fields_end].span(),
+            "expected `->` or `,` after variant",
+        ));
+    }
+    let reply: TokenStream = tokens[arrow + 2..].iter().cloned().collect();
+    if reply.is_empty() {
+        return Err(Error::new(
+            tokens[arrow + 1].span(),
+            "expected reply type after `->`",
+        ));
+    }
+    Ok(Variant {
+        tokens: tokens[..arrow].to_vec(),
+        reply: Some(reply),
+    })
+}
+
+/// Generates contents of `Input` enum.
+pub fn expand(variants: &[Variant]) -> TokenStream {
+    let mut output = TokenStream::new();
+    for variant in variants {
+        let mut tokens = variant.tokens.clone();
+        if let Some(reply) = &variant.reply {
+            let mut vars = HashMap::new();
+            vars.insert("reply", reply.clone());
+            let reply_to = template::expand("movie::ReplyTo<#reply>", &vars);
+            match tokens.pop() {
+                Some(TokenTree::Group(group)) => {
+                    let mut stream = group.stream();
+                    let needs_comma = match stream.clone().into_iter().last() {
+                        None => false,
+                        Some(last) => !is_punct(&last, ','),
+                    };
+                    if needs_comma {
+                        stream.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+                    }
+                    if group.delimiter() == Delimiter::Brace {
+                        stream.extend("reply_to:".parse::<TokenStream>().unwrap());
+                    }
+                    stream.extend(reply_to);
+                    let mut new_group = Group::new(group.delimiter(), stream);
+                    new_group.set_span(group.span());
+                    tokens.push(new_group.into());
+                }
+                // Unit variant
+                Some(name) => {
+                    tokens.push(name);
+                    tokens.push(Group::new(Delimiter::Parenthesis, reply_to).into());
+                }
+                None => unreachable!(),
+            }
+        }
+        output.extend(tokens);
+        output.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+    }
+    output
+}
+
+/// Generates methods sending the variants: declarations for the trait and their
+/// implementations for `movie::Addr<#input>`. `path` is the enum without generic
+/// arguments, used to build the variants.
+///
+/// `Variant(A, B)` becomes `fn variant(&self, arg0: A, arg1: B) -> Result<(),
+/// movie::SendError<#input>>`. Reply-bearing variants are asked instead, so the method
+/// returns `Result<Reply, movie::AskError>`. Struct-like variants keep field names.
+///
+/// Variants whose method would be shadowed by a method of `movie::Addr` or `movie::Handle`
+/// (e.g. `Send`) are rejected. Keywords (e.g. `Move`) become raw identifiers (`r#move`).
+pub fn expand_methods(
+    variants: &[Variant],
+    input: &TokenStream,
+    path: &TokenStream,
+) -> Result<(TokenStream, TokenStream), Error> {
+    let mut declarations = TokenStream::new();
+    let mut definitions = TokenStream::new();
+    for variant in variants {
+        // Docs go to the method, other attributes (e.g. `#[serde]`) stay on the variant
+        let mut docs = TokenStream::new();
+        let mut i = 0;
+        while is_punct(&variant.tokens[i], '#') {
+            let is_doc = match &variant.tokens[i + 1] {
+                TokenTree::Group(group) => group
+                    .stream()
+                    .into_iter()
+                    .next()
+                    .is_some_and(|token| token.to_string() == "doc"),
+                _ => false,
+            };
+            if is_doc {
+                docs.extend(variant.tokens[i..i + 2].iter().cloned());
+            }
+            i += 2;
+        }
+        let name = match &variant.tokens[i] {
+            TokenTree::Ident(name) => name.clone(),
+            _ => unreachable!(),
+        };
+        let method = method_name(&name)?;
+
+        // `arg0: A, ...`, and the variant built from them, `Variant(arg0, ...)`
+        let mut params = TokenStream::new();
+        let mut fields = Vec::new();
+        let mut delimiter = None;
+        if let Some(TokenTree::Group(group)) = variant.tokens.get(i + 1) {
+            let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+            for (n, field) in split_commas(&tokens).into_iter().enumerate() {
+                if field.is_empty() {
+                    continue;
+                }
+                let (field_name, field_type) = if group.delimiter() == Delimiter::Brace {
+                    // `#[attr] pub name: Type`
+                    let colon = field.iter().position(|token| is_punct(token, ':')).unwrap();
+                    (field[colon - 1].clone(), &field[colon + 1..])
+                } else {
+                    let mut start = 0;
+                    while is_punct(&field[start], '#') {
+                        start += 2;
+                    }
+                    let arg = Ident::new(&format!("arg{}", n), field[start].span());
+                    (TokenTree::from(arg), &field[start..])
+                };
+                params.extend(Some(field_name.clone()));
+                params.extend(Some(TokenTree::from(Punct::new(':', Spacing::Alone))));
+                params.extend(field_type.iter().cloned());
+                params.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+                fields.push(field_name.to_string());
+            }
+            delimiter = Some(group.delimiter());
+        }
+        if variant.reply.is_some() {
+            fields.push(match delimiter {
+                Some(Delimiter::Brace) => "reply_to: reply".to_string(),
+                _ => "reply".to_string(),
+            });
+        }
+        let message = match delimiter {
+            Some(Delimiter::Brace) => format!("#path::#variant {{ {} }}", fields.join(", ")),
+            _ if fields.is_empty() => "#path::#variant".to_string(),
+            _ => format!("#path::#variant({})", fields.join(", ")),
+        };
+
+        let mut vars = HashMap::new();
+        vars.insert("docs", docs);
+        vars.insert("method", TokenTree::from(method).into());
+        vars.insert("input", input.clone());
+        vars.insert("path", path.clone());
+        vars.insert("variant", TokenTree::from(name).into());
+        vars.insert("params", params);
+        let (signature, body) = match &variant.reply {
+            Some(reply) => {
+                vars.insert("reply", reply.clone());
+                (
+                    "fn #method(&self, #params) -> Result<#reply, movie::AskError>",
+                    format!("self.ask(|reply| {})", message),
+                )
+            }
+            None => (
+                "fn #method(&self, #params) -> Result<(), movie::SendError<#input>>",
+                format!("self.send({})", message),
+            ),
+        };
+        declarations.extend(template::expand(&format!("#docs {};", signature), &vars));
+        definitions.extend(template::expand(
+            &format!("#docs {} {{ {} }}", signature, body),
+            &vars,
+        ));
+    }
+    Ok((declarations, definitions))
+}
+
+/// Inherent methods of `movie::Addr` and `movie::Handle`, and methods of the prelude traits
+/// they implement (`Clone`, `ToOwned`, `Into` and, since edition 2021, `TryInto`). They
+/// would be called instead of the generated methods of the same name, or make the calls
+/// ambiguous.
+const TAKEN: &[&str] = &[
+    "addr",
+    "ask",
+    "ask_deferred",
+    "ask_timeout",
+    "capacity",
+    "clone",
+    "clone_into",
+    "id",
+    "into",
+    "is_alive",
+    "is_finished",
+    "join",
+    "queue_len",
+    "request_stop",
+    "send",
+    "send_after",
+    "send_at",
+    "send_interval",
+    "stop",
+    "stop_timeout",
+    "subscribe",
+    "to_owned",
+    "try_into",
+    "try_send",
+];
+
+/// Keywords, which can be used as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "union", "unsafe", "unsized",
+    "use", "virtual", "where", "while", "yield",
+];
+
+/// Name of the method sending `variant`, e.g. `get_state` for `GetState`.
+fn method_name(variant: &Ident) -> Result<Ident, Error> {
+    let name = snake_case(&variant.to_string());
+    if TAKEN.contains(&name.as_str()) {
+        return Err(Error::new(
+            variant.span(),
+            format!(
+                "variant `{}` would get method `{}()`, which the handle already has; rename it",
+                variant, name
+            ),
+        ));
+    }
+    // Keywords that can't be raw identifiers
+    if ["crate", "self", "super"].contains(&name.as_str()) {
+        return Err(Error::new(
+            variant.span(),
+            format!(
+                "variant `{}` would get method `{}()`, which is a keyword; rename it",
+                variant, name
+            ),
+        ));
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        Ok(Ident::new_raw(&name, variant.span()))
+    } else {
+        Ok(Ident::new(&name, variant.span()))
+    }
+}
+
+/// Converts `CamelCase` to `snake_case`. Runs of capitals are kept together, so
+/// `HTTPRequest` becomes `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let after_lower = i > 0 && !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            // The last capital of a run starts a new word, e.g. `R` in `HTTPRequest`
+            let ends_run = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_lower || ends_run {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Splits tokens at commas that are not inside `<...>`.
+pub fn split_commas(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut angle_depth = 0;
+    for i in 0..tokens.len() {
+        match &tokens[i] {
+            TokenTree::Punct(p) if p.as_char() == '<' => angle_depth += 1,
+            TokenTree::Punct(p) if p.as_char() == '>' && !(i > 0 && is_arrow(tokens, i - 1)) => {
+                angle_depth -= 1
+            }
+            TokenTree::Punct(p) if p.as_char() == ',' && angle_depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+/// Is there `->` at `tokens[i]`?
+fn is_arrow(tokens: &[TokenTree], i: usize) -> bool {
+    match (&tokens[i], tokens.get(i + 1)) {
+        (TokenTree::Punct(minus), Some(TokenTree::Punct(gt))) => {
+            minus.as_char() == '-' && minus.spacing() == Spacing::Joint && gt.as_char() == '>'
+        }
+        _ => false,
+    }
+}
@@ -3,10 +3,18 @@
 //! `movie_derive` - crate containing procedural macros.
 
 extern crate proc_macro;
-use proc_macro::TokenStream;
+use proc_macro::{Delimiter, Literal, TokenStream, TokenTree};
 
 use std::collections::HashMap;
 
+mod attribute;
+mod generics;
+mod input;
+mod local_data;
+mod parse;
+mod template;
+mod timers;
+
 #[proc_macro]
 /// Macro that generates module `ActorName`, which contains structs `Actor` and `Input`.
 pub fn actor(input: TokenStream) -> TokenStream {
@@ -18,189 +26,600 @@ pub fn actor_dbg(input: TokenStream) -> TokenStream {
     actor_internal(input, true)
 }
 
-// Input: "SimplestActor input : Ping , on_message : Ping => Pong ,"
-fn actor_internal(input: TokenStream, debug: bool) -> TokenStream {
-    let input = input.to_string();
+#[proc_macro_attribute]
+/// Attribute form of `actor!`, to be put on a struct (actor's data) and on its impl block
+/// (handlers). Re-exported as `movie::attr::actor`.
+pub fn actor_attribute(args: TokenStream, item: TokenStream) -> TokenStream {
+    match attribute::expand(args, item) {
+        Ok(output) => output,
+        Err(err) => err.to_compile_error(),
+    }
+}
 
+// Input: SimplestActor input: Ping, on_message: Ping => Pong,
+fn actor_internal(input: TokenStream, debug: bool) -> TokenStream {
     if debug {
         eprintln!("Input:");
         eprintln!("{}", input);
     }
 
     // PART ONE
-    // Locate attributes inside input string
-
-    let supported_attributes = vec![
-        // attr name, default value
-        ("public_visibility", ""),
-        ("docs", ""),
-        ("input", ""),
-        ("input_derive", ""),
-        ("data", ""),
-        ("on_init", ""),
-        ("on_message", ""),
-        ("tick_interval", "100"),
-        ("on_tick", ""),
-        ("on_stop", ""),
-        ("spawner", "std::thread::spawn"),
-        ("spawner_return_type", "std::thread::JoinHandle<()>"),
-        ("custom_code", ""),
-    ];
-
-    // locations = [(start, attr name, start of content), ...]
-    let mut locations = vec![(0, "name", 0)];
-    for attr in &supported_attributes {
-        let attr = attr.0;
-        // Any of the following cases may happen:
-        let search_strings = &[
-            format!("\n{} :\n", attr),
-            format!(" {} :\n", attr),
-            format!("\n{} : ", attr),
-            format!(" {} : ", attr),
-            format!("\n{}\n:\n", attr),
-            format!(" {}\n:\n", attr),
-            format!("\n{}\n: ", attr),
-            format!(" {}\n: ", attr),
-        ];
-        for search_str in search_strings {
-            let pos = input.find(search_str);
-            if let Some(pos) = pos {
-                locations.push((pos, attr, pos + search_str.len()));
-                break;
-            }
-        }
-    }
+    // Parse attributes
 
-    // PART TWO
-    // Turn array of locations to array of values
-
-    // attrs = {
-    //     "input": "Ping ,"
-    //     "name": "SimplestActor",
-    //     "on_message": "Ping => Pong ,",
-    // }
-    let mut attrs: HashMap<&str, String> = HashMap::new();
-
-    locations.sort_unstable();
-
-    // locations = [(start, attr name, start of content), ...]
-    for i in 0..locations.len() {
-        let value = if i == locations.len() - 1 {
-            // We are parsing the last segment
-            &input[locations[i].2..]
-        } else {
-            // Start of the next segment is this one's ends
-            &input[locations[i].2..locations[i + 1].0]
-        };
-        attrs.insert(locations[i].1, value.to_string());
-    }
+    let def = match parse::parse(input) {
+        Ok(def) => def,
+        Err(err) => return err.to_compile_error(),
+    };
 
     if debug {
         eprintln!("Parsed attributes:");
-        eprintln!("{:?}", &attrs);
+        eprintln!("{:?}", &def);
     }
 
-    // PART THREE
-    // Assign default values for missing optional supported attrs
+    // PART TWO
+    // Generate code
+
+    // Prepare token streams used later
+    let public_visibility = match def.get("public_visibility") {
+        Some(value) if value.to_string() == "true" => "pub".parse().unwrap(),
+        _ => TokenStream::new(),
+    };
+    let input_derive = match def.get("input_derive") {
+        Some(derives) if !derives.is_empty() => {
+            let mut vars = HashMap::new();
+            vars.insert("derives", derives);
+            template::expand("#[derive(#derives)]", &vars)
+        }
+        _ => TokenStream::new(),
+    };
 
-    for attr in &supported_attributes {
-        attrs.entry(attr.0).or_insert(attr.1.to_string());
-    }
+    let mailbox = match mailbox(def.get("mailbox_capacity"), def.get("mailbox_overflow")) {
+        Ok(mailbox) => mailbox,
+        Err(err) => return err.to_compile_error(),
+    };
 
-    // PART FOUR
-    // Generate code
+    let tick_schedule = match tick_schedule(def.get("tick_schedule")) {
+        Ok(tick_schedule) => tick_schedule,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let variants = match input::parse(def.get_or("input", "")) {
+        Ok(variants) => variants,
+        Err(err) => return err.to_compile_error(),
+    };
 
-    // Prepare strings used later
-    let public_visibility = if attrs["public_visibility"].contains("true") {
-        "pub".to_string()
+    let output = match output(&def) {
+        Ok(output) => output,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let (timer_starts, timer_arms) = match def.get("timers") {
+        Some(timers) => match timers::expand(timers) {
+            Ok(timers) => timers,
+            Err(err) => return err.to_compile_error(),
+        },
+        None => (TokenStream::new(), TokenStream::new()),
+    };
+
+    let local_data = match def.get("local_data") {
+        Some(_) if !def.generics.is_empty() => {
+            return parse::Error::new(
+                def.name.span(),
+                "`local_data` is not supported on generic actors",
+            )
+            .to_compile_error()
+        }
+        Some(local_data) => match local_data::expand(local_data) {
+            Ok(local_data) => Some(local_data),
+            Err(err) => return err.to_compile_error(),
+        },
+        None => None,
+    };
+
+    let returns = def.get("returns");
+
+    let mut vars = HashMap::new();
+    // attrs
+    vars.insert("name", TokenTree::from(def.name.clone()).into());
+    vars.insert(
+        "name_str",
+        TokenTree::from(Literal::string(&def.name.to_string())).into(),
+    );
+    vars.insert("docs", def.get_or("docs", ""));
+    vars.insert("input", input::expand(&variants));
+    vars.insert("data", def.get_or("data", ""));
+    vars.insert("on_init", def.get_or("on_init", ""));
+    vars.insert("on_message", def.get_or("on_message", ""));
+    vars.insert("tick_interval", def.get_or("tick_interval", "100"));
+    vars.insert("on_tick", def.get_or("on_tick", ""));
+    vars.insert("on_stop", def.get_or("on_stop", ""));
+    vars.insert("custom_code", def.get_or("custom_code", ""));
+    // prepared token streams
+    vars.insert("output", output);
+    vars.insert("tick_schedule", tick_schedule);
+    vars.insert("timer_starts", timer_starts);
+    vars.insert("timer_arms", timer_arms.clone());
+    vars.insert("public_visibility", public_visibility);
+    vars.insert("input_derive", input_derive);
+    vars.insert("mailbox", mailbox);
+    // generics
+    let generics = &def.generics;
+    vars.insert("generics", generics.declaration());
+    vars.insert("impl_generics", generics.impl_params());
+    vars.insert("alias_generics", generics.alias_params());
+    vars.insert("args", generics.args());
+    vars.insert("where_clause", generics.where_clause());
+    vars.insert("phantom_variant", generics.phantom_variant());
+    vars.insert("phantom_arm", generics.phantom_arm("Input"));
+    // `start()` is only available for parameters that can be sent to a new thread
+    let start_bounds = if generics.is_empty() {
+        TokenStream::new()
     } else {
-        "".to_string()
+        template::expand(
+            "where
+                Self: Send + 'static,
+                Input #args: Send + 'static,
+                <Self as movie::Actor>::Output: Send + 'static,",
+            &vars,
+        )
+    };
+    vars.insert("start_bounds", start_bounds);
+    let (declarations, definitions) = match input::expand_methods(
+        &variants,
+        &template::expand("Input #args", &vars),
+        &"Input".parse().unwrap(),
+    ) {
+        Ok(methods) => methods,
+        Err(err) => return err.to_compile_error(),
+    };
+    vars.insert("method_declarations", declarations);
+    vars.insert("method_definitions", definitions);
+    let spawner_return_type = match def.get("spawner_return_type") {
+        Some(spawner_return_type) => spawner_return_type,
+        None => template::expand(
+            "std::thread::JoinHandle<<Actor #args as movie::Actor>::Output>",
+            &vars,
+        ),
+    };
+    vars.insert("spawner_return_type", spawner_return_type);
+    // Without `spawner`, the thread is named and can fail to spawn without panicking
+    let start = match def.get("spawner") {
+        Some(spawner) => {
+            vars.insert("spawner", spawner);
+            template::expand(
+                "
+                pub fn start(self) -> Handle #args #start_bounds {
+                    movie::start_with(self, #spawner)
+                }
+                ",
+                &vars,
+            )
+        }
+        None => template::expand(
+            "
+            /// Spawns the actor in a new thread, see `movie::start()`.
+            pub fn start(self) -> Handle #args #start_bounds {
+                movie::start(self)
+            }
+            /// Like `start()`, but returns the error if the thread can't be spawned.
+            pub fn try_start(self) -> std::io::Result<Handle #args> #start_bounds {
+                movie::try_start(self)
+            }
+            ",
+            &vars,
+        ),
+    };
+    vars.insert("start", start);
+    let stack_size = match def.get("stack_size") {
+        Some(stack_size) => {
+            vars.insert("stack_size", stack_size);
+            template::expand("Some(#stack_size)", &vars)
+        }
+        None => "None".parse().unwrap(),
     };
-    let input_derive = if attrs["input_derive"].len() > 0 {
-        format!("#[derive({})]", attrs["input_derive"])
+    vars.insert("stack_size", stack_size);
+    let thread_name = match def.get("instance_id") {
+        Some(instance_id) => {
+            vars.insert("instance_id", instance_id);
+            template::expand(
+                "
+                fn thread_name(&self) -> String {
+                    format!(\"{}-{}\", #name_str, #instance_id)
+                }
+                ",
+                &vars,
+            )
+        }
+        None => TokenStream::new(),
+    };
+    vars.insert("thread_name", thread_name);
+    vars.insert(
+        "returns",
+        returns.clone().unwrap_or_else(|| "()".parse().unwrap()),
+    );
+    // Without `returns`, the value of `on_stop` is ignored
+    let on_stop = match returns {
+        Some(_) => template::expand("(|| -> #returns { #on_stop })()", &vars),
+        None => template::expand("(|| { #on_stop })();", &vars),
+    };
+    vars.insert("on_stop", on_stop);
+
+    // Variables declared in on_init are visible in other handlers. To keep it that way,
+    // they are all put in one function, overriding `movie::Actor::run()`. With
+    // `local_data`, they are just local to on_init.
+    let has_local_data = local_data.is_some();
+    let on_init_tokens: Vec<TokenTree> = def.get_or("on_init", "").into_iter().collect();
+    // Only `let` statements, not `if let` or `while let`. Those in blocks are not visible
+    // anyway.
+    let locals = !has_local_data
+        && (0..on_init_tokens.len()).any(|i| {
+            let statement_start = match i.checked_sub(1).map(|prev| &on_init_tokens[prev]) {
+                None => true,
+                Some(TokenTree::Punct(p)) => p.as_char() == ';',
+                Some(TokenTree::Group(g)) => g.delimiter() == Delimiter::Brace,
+                Some(_) => false,
+            };
+            statement_start && on_init_tokens[i].to_string() == "let"
+        });
+    let methods = if locals {
+        vars.insert("local_struct", TokenStream::new());
+        template::expand(
+            "
+            const RUN_ONLY: bool = true;
+            fn on_message(&mut self, ctx: &mut movie::Context<Self::Input>, message: Self::Input) {
+                unreachable!(\"on_init of {} declares variables, use run()\", #name_str)
+            }
+            fn on_stop(self, ctx: &mut movie::Context<Self::Input>) -> #returns {
+                unreachable!(\"on_init of {} declares variables, use run()\", #name_str)
+            }
+            fn run(
+                mut self,
+                mut ctx: movie::Context<Self::Input>,
+                mailbox: movie::Mailbox<Self::Input>,
+            ) -> #returns {
+                ctx.set_tick_interval(<Self as movie::Actor>::TICK_INTERVAL);
+                ctx.set_tick_schedule(<Self as movie::Actor>::TICK_SCHEDULE);
+                #timer_starts
+                #on_init
+                // Handlers are run inside a closure, so that `return` in them leaves the
+                // handler.
+                movie::actor::run_loop(&mut ctx, &mailbox, |ctx, event| match event {
+                    movie::actor::Event::Message(message) => {
+                        use Input::*;
+                        match message {
+                            #on_message
+                            #phantom_arm
+                        }
+                    }
+                    movie::actor::Event::Tick => {
+                        #on_tick
+                    }
+                    movie::actor::Event::Timer(timer) => match timer {
+                        #timer_arms
+                        _ => (),
+                    },
+                });
+                #on_stop
+            }
+            ",
+            &vars,
+        )
+    } else if let Some((local_fields, local_inits)) = local_data {
+        vars.insert("local_fields", local_fields);
+        vars.insert("local_inits", local_inits);
+        // `Local` stays on the actor's thread, in a thread-local (see
+        // `movie_utils::local_data`), and every handler takes it out while it runs. Handlers are run inside a closure, so that `return` in them
+        // leaves the handler, and not before `Local` is put back.
+        vars.insert(
+            "local_struct",
+            template::expand(
+                "
+                /// Thread-local state of the actor (`local_data`), built in `on_init`.
+                struct Local {
+                    #local_fields
+                }
+
+                fn __movie_take_local(ctx: &movie::Context<Input>) -> Local {
+                    movie::local_data::take(ctx.addr().id()).unwrap_or_else(|| {
+                        panic!(\"{} has local_data, its handlers must run on one thread\", #name_str)
+                    })
+                }
+
+                fn __movie_put_local(ctx: &movie::Context<Input>, local: Local) {
+                    movie::local_data::put(ctx.addr().id(), local);
+                }
+                ",
+                &vars,
+            ),
+        );
+        template::expand(
+            "
+            const THREAD_BOUND: bool = true;
+            fn on_init(&mut self, ctx: &mut movie::Context<Self::Input>) {
+                let mut local = Local { #local_inits };
+                (|| {
+                    #timer_starts
+                    #on_init
+                })();
+                __movie_put_local(ctx, local);
+            }
+            fn on_message(&mut self, ctx: &mut movie::Context<Self::Input>, message: Self::Input) {
+                let mut local = __movie_take_local(ctx);
+                (|| {
+                    use Input::*;
+                    match message {
+                        #on_message
+                    }
+                })();
+                __movie_put_local(ctx, local);
+            }
+            fn on_tick(&mut self, ctx: &mut movie::Context<Self::Input>) {
+                let mut local = __movie_take_local(ctx);
+                (|| {
+                    #on_tick
+                })();
+                __movie_put_local(ctx, local);
+            }
+            fn on_stop(mut self, ctx: &mut movie::Context<Self::Input>) -> #returns {
+                let mut local = __movie_take_local(ctx);
+                #on_stop
+            }
+            ",
+            &vars,
+        )
     } else {
-        "".to_string()
+        vars.insert("local_struct", TokenStream::new());
+        template::expand(
+            "
+            fn on_init(&mut self, ctx: &mut movie::Context<Self::Input>) {
+                #timer_starts
+                #on_init
+            }
+            fn on_message(&mut self, ctx: &mut movie::Context<Self::Input>, message: Self::Input) {
+                use Input::*;
+                match message {
+                    #on_message
+                    #phantom_arm
+                }
+            }
+            fn on_tick(&mut self, ctx: &mut movie::Context<Self::Input>) {
+                #on_tick
+            }
+            fn on_stop(mut self, ctx: &mut movie::Context<Self::Input>) -> #returns {
+                #on_stop
+            }
+            ",
+            &vars,
+        )
     };
+    let mut methods = methods;
+    if !timer_arms.is_empty() {
+        let on_timer = if has_local_data {
+            "
+            fn on_timer(&mut self, ctx: &mut movie::Context<Self::Input>, timer: &'static str) {
+                let mut local = __movie_take_local(ctx);
+                (|| match timer {
+                    #timer_arms
+                    _ => (),
+                })();
+                __movie_put_local(ctx, local);
+            }
+            "
+        } else {
+            "
+            fn on_timer(&mut self, ctx: &mut movie::Context<Self::Input>, timer: &'static str) {
+                match timer {
+                    #timer_arms
+                    _ => (),
+                }
+            }
+            "
+        };
+        methods.extend(template::expand(on_timer, &vars));
+    }
+    vars.insert("methods", methods);
 
-    // TODO: Consider rewriting to quote!()
-    let output = format!(
+    let output = template::expand(
         "
-        {docs}
-        {public_visibility} mod {name} {{
+        #docs
+        #[allow(non_snake_case)]
+        #public_visibility mod #name {
         use super::*;
 
-        {custom_code}
-
-        pub struct Actor {{
-            {data}
-        }}
-
-        {input_derive}
-        pub enum Input {{
-            {input}
-        }}
-
-        pub type Handle = movie::Handle<{spawner_return_type}, Input>;
-
-        impl Actor {{
-            pub fn start(mut self) -> Handle
-            {{
-                let (tx_ota, rx_ota) = std::sync::mpsc::channel(); // owner-to-actor data
-                let (tx_kill, rx_kill) = std::sync::mpsc::channel(); // owner-to-actor stop requests
-                let handle = {spawner}(move || {{
-                    {on_init} // on_init is not separated as this is the simplest way to
-                              // implement thread-local data. This may change in later (breaking)
-                              // updates
-                    let mut running = true;
-                    while running {{
-                        while let Ok(message) = rx_ota.try_recv() {{
-                            use Input::*;
-                            match message {{
-                                {on_message}
-                            }};
-                        }}
-                        if let Ok(_) = rx_kill.try_recv() {{
-                            running = false;
-                            {{
-                                {on_stop}
-                            }};
-                        }}
-                        {{
-                            {on_tick}
-                        }};
-                        use std::thread::sleep;
-                        use std::time::Duration;
-                        sleep(Duration::from_millis({tick_interval}));
-                    }}
-                }});
-                movie::Handle {{
-                    join_handle: handle,
-                    tx: tx_ota,
-                    kill: tx_kill,
-                }}
-            }}
-        }}
-        }}",
-        // attrs
-        name = attrs["name"],
-        docs = attrs["docs"],
-        input = attrs["input"],
-        data = attrs["data"],
-        on_init = attrs["on_init"],
-        on_message = attrs["on_message"],
-        tick_interval = attrs["tick_interval"],
-        on_tick = attrs["on_tick"],
-        on_stop = attrs["on_stop"],
-        spawner = attrs["spawner"],
-        spawner_return_type = attrs["spawner_return_type"],
-        custom_code = attrs["custom_code"],
-        // prepared strings
-        public_visibility = public_visibility,
-        input_derive = input_derive,
+        #custom_code
+
+        pub struct Actor #generics #where_clause {
+            #data
+        }
+
+        #local_struct
+
+        #input_derive
+        pub enum Input #generics #where_clause {
+            #input
+            #phantom_variant
+        }
+
+        #output
+
+        pub type Handle #alias_generics = movie::Handle<#spawner_return_type, Input #args>;
+
+        /// Methods sending each variant of `Input`, e.g. `handle.change_source(name)`
+        /// for `ChangeSource(String)`. Implemented for `Addr`, and so usable on `Handle`.
+        pub trait HandleExt #impl_generics #where_clause {
+            #method_declarations
+        }
+
+        impl #impl_generics HandleExt #args for movie::Addr<Input #args> #where_clause {
+            #method_definitions
+        }
+
+        impl #impl_generics Actor #args #where_clause {
+            #start
+            /// Runs the actor on the current thread until it stops, see
+            /// `movie::run_here()`.
+            pub fn run_here(self) -> <Self as movie::Actor>::Output {
+                movie::run_here(self)
+            }
+            /// Runs the actor on the current thread, after giving its handle to `f`, see
+            /// `movie::run_with_handle()`.
+            pub fn run_with_handle(
+                self,
+                f: impl FnOnce(
+                    movie::Handle<
+                        movie::local::LocalJoinHandle<<Self as movie::Actor>::Output>,
+                        <Self as movie::Actor>::Input,
+                    >,
+                ),
+            ) {
+                movie::run_with_handle(self, f)
+            }
+        }
+
+        #[allow(unused_variables, unused_mut)]
+        impl #impl_generics movie::Actor for Actor #args #where_clause {
+            type Input = Input #args;
+            type Output = #returns;
+            const TICK_INTERVAL: std::time::Duration =
+                std::time::Duration::from_millis(#tick_interval);
+            const TICK_SCHEDULE: movie::TickSchedule = #tick_schedule;
+            const STACK_SIZE: Option<usize> = #stack_size;
+            fn name() -> &'static str {
+                #name_str
+            }
+            #thread_name
+            fn mailbox() -> (movie::Addr<Self::Input>, movie::Mailbox<Self::Input>) {
+                #mailbox
+            }
+            #methods
+        }
+        }",
+        &vars,
     );
     if debug {
         eprintln!("Generated code:");
         eprintln!("{}", output);
     }
-    output.parse().unwrap()
+    output
+}
+
+/// `Output` enum, if `output` is set. Variants are declared like in `input`, but can't be
+/// reply-bearing. `Clone` is always derived, as every subscriber gets a copy of an event.
+fn output(def: &parse::ActorDef) -> Result<TokenStream, parse::Error> {
+    let variants = match def.get("output") {
+        Some(output) => input::parse(output)?,
+        None => {
+            if let Some(derives) = def.get("output_derive") {
+                let span = derives
+                    .into_iter()
+                    .next()
+                    .map_or_else(|| def.name.span(), |token| token.span());
+                return Err(parse::Error::new(span, "`output_derive` requires `output`"));
+            }
+            return Ok(TokenStream::new());
+        }
+    };
+    if let Some(reply) = variants.iter().find_map(|variant| variant.reply.clone()) {
+        return Err(parse::Error::new(
+            reply.into_iter().next().unwrap().span(),
+            "`output` variants can't be reply-bearing",
+        ));
+    }
+    let generics = &def.generics;
+    let mut vars = HashMap::new();
+    vars.insert("variants", input::expand(&variants));
+    vars.insert("generics", generics.declaration());
+    vars.insert("impl_generics", generics.impl_params());
+    vars.insert("args", generics.args());
+    vars.insert("where_clause", generics.where_clause());
+    vars.insert("phantom_variant", generics.phantom_variant());
+    vars.insert("derives", def.get_or("output_derive", ""));
+    Ok(template::expand(
+        "
+        /// Events emitted with `ctx.emit()`, see `Addr::subscribe()`.
+        #[derive(Clone, #derives)]
+        pub enum Output #generics #where_clause {
+            #variants
+            #phantom_variant
+        }
+
+        impl #impl_generics movie::HasOutput for Input #args #where_clause {
+            type Output = Output #args;
+        }
+        ",
+        &vars,
+    ))
+}
+
+/// Path of the `movie::TickSchedule` variant named in `tick_schedule`.
+fn tick_schedule(schedule: Option<TokenStream>) -> Result<TokenStream, parse::Error> {
+    let schedule = match schedule {
+        Some(schedule) => schedule.into_iter().next().unwrap(),
+        None => return Ok("movie::TickSchedule::FixedDelay".parse().unwrap()),
+    };
+    if !["FixedDelay", "CatchUp", "Skip"].contains(&schedule.to_string().as_str()) {
+        return Err(parse::Error::new(
+            schedule.span(),
+            "expected `FixedDelay`, `CatchUp` or `Skip`",
+        ));
+    }
+    let mut vars = HashMap::new();
+    vars.insert("schedule", schedule.into());
+    Ok(template::expand("movie::TickSchedule::#schedule", &vars))
+}
+
+/// Expression creating the actor's mailbox, based on `mailbox_capacity` and
+/// `mailbox_overflow`.
+fn mailbox(
+    capacity: Option<TokenStream>,
+    overflow: Option<TokenStream>,
+) -> Result<TokenStream, parse::Error> {
+    let overflow = match overflow {
+        Some(overflow) => {
+            let policy = overflow.into_iter().next().unwrap();
+            let policies = ["Block", "Fail", "DropNewest", "DropOldest"];
+            if !policies.contains(&policy.to_string().as_str()) {
+                return Err(parse::Error::new(
+                    policy.span(),
+                    "expected `Block`, `Fail`, `DropNewest` or `DropOldest`",
+                ));
+            }
+            Some(policy)
+        }
+        None => None,
+    };
+    let capacity = match capacity {
+        Some(capacity) => capacity,
+        None => {
+            if let Some(policy) = overflow {
+                return Err(parse::Error::new(
+                    policy.span(),
+                    "`mailbox_overflow` requires `mailbox_capacity`",
+                ));
+            }
+            return Ok("movie::mailbox()".parse().unwrap());
+        }
+    };
+    // `bounded_mailbox()` would panic when the actor is started
+    let literal = capacity.clone().into_iter().next().unwrap();
+    let is_zero = match &literal {
+        TokenTree::Literal(literal) => parse::integer_value(literal) == Some(0),
+        _ => false,
+    };
+    if is_zero {
+        return Err(parse::Error::new(
+            literal.span(),
+            "`mailbox_capacity` must be at least 1",
+        ));
+    }
+    let mut vars = HashMap::new();
+    vars.insert("capacity", capacity);
+    vars.insert(
+        "overflow",
+        overflow.map_or_else(|| "Block".parse().unwrap(), TokenStream::from),
+    );
+    Ok(template::expand(
+        "movie::bounded_mailbox(#capacity, movie::Overflow::#overflow)",
+        &vars,
+    ))
 }
@@ -0,0 +1,122 @@
+//! Parsing and expansion of `local_data` attribute.
+//!
+//! Fields are written like struct fields with an initial value: `name: Type = expression,`.
+//! They become fields of the `Local` struct, which is built on the actor's thread.
+
+use crate::parse::{is_punct, Error};
+use crate::template;
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+
+/// Returns field declarations (for `struct Local`) and their initializers (for `Local {
+/// ... }`).
+pub fn expand(input: TokenStream) -> Result<(TokenStream, TokenStream), Error> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut fields = TokenStream::new();
+    let mut inits = TokenStream::new();
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        // Attributes, e.g. doc comments
+        let attrs_start = i;
+        while is_punct(&tokens[i], '#') && is_bracket_group(tokens.get(i + 1)) {
+            i += 2;
+            if i == tokens.len() {
+                return Err(Error::new(
+                    tokens[i - 1].span(),
+                    "expected field after attribute",
+                ));
+            }
+        }
+        let name = match (&tokens[i], tokens.get(i + 1)) {
+            (TokenTree::Ident(name), Some(TokenTree::Punct(colon)))
+                if colon.as_char() == ':' && colon.spacing() == Spacing::Alone =>
+            {
+                name.clone()
+            }
+            (other, _) => {
+                return Err(Error::new(
+                    other.span(),
+                    "expected field, e.g. `name: Type = expression,`",
+                ))
+            }
+        };
+        if names.contains(&name.to_string()) {
+            return Err(Error::new(
+                name.span(),
+                format!("duplicate field `{}`", name),
+            ));
+        }
+        names.push(name.to_string());
+
+        // The type ends with `=` outside of angle brackets
+        let type_start = i + 2;
+        let mut angle_depth = 0;
+        let mut eq = None;
+        for j in type_start..tokens.len() {
+            match &tokens[j] {
+                TokenTree::Punct(p) if p.as_char() == '<' => angle_depth += 1,
+                // `->` is not a closing bracket
+                TokenTree::Punct(p) if p.as_char() == '>' && !is_punct(&tokens[j - 1], '-') => {
+                    angle_depth -= 1
+                }
+                TokenTree::Punct(p) if p.as_char() == '=' && angle_depth == 0 => {
+                    eq = Some(j);
+                    break;
+                }
+                TokenTree::Punct(p) if p.as_char() == ',' && angle_depth == 0 => break,
+                _ => (),
+            }
+        }
+        let eq = match eq {
+            Some(eq) if eq > type_start => eq,
+            Some(eq) => return Err(Error::new(tokens[eq].span(), "expected field type")),
+            None => {
+                return Err(Error::new(
+                    name.span(),
+                    format!("expected initial value, e.g. `{}: Type = expression`", name),
+                ))
+            }
+        };
+
+        // The expression ends with a comma followed by the next field, or by nothing
+        let start = eq + 1;
+        let end = (start..tokens.len())
+            .find(|&j| is_punct(&tokens[j], ',') && is_field_start(&tokens, j + 1))
+            .unwrap_or(tokens.len());
+        if start == end {
+            return Err(Error::new(tokens[eq].span(), "expected initial value"));
+        }
+
+        let mut vars = HashMap::new();
+        vars.insert("attrs", tokens[attrs_start..i].iter().cloned().collect());
+        vars.insert("name", TokenTree::from(name).into());
+        vars.insert("type", tokens[type_start..eq].iter().cloned().collect());
+        vars.insert("value", tokens[start..end].iter().cloned().collect());
+        fields.extend(template::expand("#attrs #name: #type,", &vars));
+        inits.extend(template::expand("#name: #value,", &vars));
+
+        i = end + 1;
+    }
+    Ok((fields, inits))
+}
+
+fn is_bracket_group(token: Option<&TokenTree>) -> bool {
+    match token {
+        Some(TokenTree::Group(group)) => group.delimiter() == Delimiter::Bracket,
+        _ => false,
+    }
+}
+
+/// Does a field start at `tokens[i]` (or do the tokens end there)?
+fn is_field_start(tokens: &[TokenTree], i: usize) -> bool {
+    match (tokens.get(i), tokens.get(i + 1)) {
+        (None, _) => true,
+        (Some(hash), next) if is_punct(hash, '#') => is_bracket_group(next),
+        (Some(TokenTree::Ident(_)), Some(TokenTree::Punct(colon))) => {
+            colon.as_char() == ':' && colon.spacing() == Spacing::Alone
+        }
+        _ => false,
+    }
+}
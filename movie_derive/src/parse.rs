@@ -0,0 +1,571 @@
+//! Parsing of `actor!` input.
+//!
+//! Input is a name, optionally with generics (see `generics.rs`), followed by attributes,
+//! e.g. `SimpleActor input: Ping, on_message: Ping => (),`. An attribute starts with one of
+//! the supported names followed by a single colon. Everything up to the next attribute
+//! is its value. Values are then checked according to their kind, so that typos and
+//! misplaced tokens are reported where they happen, not somewhere in generated code.
+
+use crate::generics::Generics;
+use proc_macro::{Delimiter, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+
+/// Compile error pointing at a span of the macro input.
+pub struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Error {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Expands to `::core::compile_error!("...");` with all tokens carrying the error
+    /// span, so that the compiler reports it at the offending token.
+    pub fn to_compile_error(&self) -> TokenStream {
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+        let mut args =
+            proc_macro::Group::new(Delimiter::Parenthesis, TokenTree::from(message).into());
+        args.set_span(self.span);
+        let mut tokens = Vec::new();
+        for segment in &["core", "compile_error"] {
+            let mut colon1 = Punct::new(':', Spacing::Joint);
+            colon1.set_span(self.span);
+            let mut colon2 = Punct::new(':', Spacing::Alone);
+            colon2.set_span(self.span);
+            tokens.push(TokenTree::from(colon1));
+            tokens.push(TokenTree::from(colon2));
+            tokens.push(TokenTree::from(Ident::new(segment, self.span)));
+        }
+        let mut semicolon = Punct::new(';', Spacing::Alone);
+        semicolon.set_span(self.span);
+        tokens.push(bang.into());
+        tokens.push(args.into());
+        tokens.push(semicolon.into());
+        tokens.into_iter().collect()
+    }
+}
+
+/// Kind of value an attribute accepts. Decides how its tokens are checked.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    /// `true` or `false`.
+    Bool,
+    /// Integer literal, with or without a suffix. Checked with `integer_value()` and
+    /// replaced by the unsuffixed literal.
+    Integer,
+    /// Single identifier, e.g. enum variant.
+    Ident,
+    /// Doc comments (`/// ...`).
+    Docs,
+    /// Comma-separated variants, paths or types, without any `name:` inside.
+    List,
+    /// Struct fields (`pub name: Type,`).
+    Fields,
+    /// Match arms (`Pattern => expression,`).
+    Arms,
+    /// Statements or items.
+    Code,
+    /// Struct fields with initial values (`name: Type = expression,`), checked in
+    /// `local_data.rs`.
+    InitializedFields,
+}
+
+const ATTRIBUTES: &[(&str, Kind)] = &[
+    ("public_visibility", Kind::Bool),
+    ("docs", Kind::Docs),
+    ("input", Kind::List),
+    ("input_derive", Kind::List),
+    ("output", Kind::List),
+    ("output_derive", Kind::List),
+    ("data", Kind::Fields),
+    ("local_data", Kind::InitializedFields),
+    ("on_init", Kind::Code),
+    ("on_message", Kind::Arms),
+    ("tick_interval", Kind::Integer),
+    ("tick_schedule", Kind::Ident),
+    ("mailbox_capacity", Kind::Integer),
+    ("mailbox_overflow", Kind::Ident),
+    ("on_tick", Kind::Code),
+    ("timers", Kind::Arms),
+    ("on_stop", Kind::Code),
+    ("returns", Kind::List),
+    ("spawner", Kind::List),
+    ("spawner_return_type", Kind::List),
+    ("stack_size", Kind::Integer),
+    ("instance_id", Kind::Code),
+    ("custom_code", Kind::Code),
+];
+
+/// Parsed `actor!` input.
+pub struct ActorDef {
+    pub name: Ident,
+    pub generics: Generics,
+    attrs: HashMap<&'static str, Vec<TokenTree>>,
+}
+
+impl ActorDef {
+    /// Value of the attribute, if it was present in the input.
+    pub fn get(&self, attr: &str) -> Option<TokenStream> {
+        self.attrs
+            .get(attr)
+            .map(|tokens| tokens.iter().cloned().collect())
+    }
+
+    /// Value of the attribute, or `default` parsed as Rust code if it was not present.
+    pub fn get_or(&self, attr: &str, default: &str) -> TokenStream {
+        self.get(attr)
+            .unwrap_or_else(|| default.parse().expect("invalid default value"))
+    }
+}
+
+impl std::fmt::Debug for ActorDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        map.entry(&"name", &self.name.to_string());
+        for (attr, _) in ATTRIBUTES {
+            if let Some(value) = self.get(attr) {
+                map.entry(attr, &value.to_string());
+            }
+        }
+        map.finish()
+    }
+}
+
+pub fn parse(input: TokenStream) -> Result<ActorDef, Error> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+
+    let name = match tokens.first() {
+        Some(TokenTree::Ident(ident)) => ident.clone(),
+        Some(other) => return Err(Error::new(other.span(), "expected actor name")),
+        None => {
+            return Err(Error::new(
+                Span::call_site(),
+                "expected actor name, e.g. `actor! { MyActor }`",
+            ))
+        }
+    };
+
+    // starts = [(index of attribute name, kind), ...]
+    let nested = nested(&tokens);
+    let starts: Vec<(usize, Kind)> = (1..tokens.len())
+        .filter(|&i| !nested[i])
+        .filter_map(|i| attribute_at(&tokens, i).map(|kind| (i, kind)))
+        .collect();
+
+    // Anything between the name and the first attribute, other than generics, is a
+    // mistake.
+    let first_attr = starts.first().map_or(tokens.len(), |start| start.0);
+    let generics = match tokens.get(1) {
+        Some(token) if is_punct(token, '<') || token.to_string() == "where" => {
+            Generics::parse(&tokens[1..first_attr])?
+        }
+        _ if first_attr > 1 => {
+            return Err(unexpected(&tokens, 1, "expected attribute, e.g. `input:`"))
+        }
+        _ => Generics::default(),
+    };
+
+    let mut attrs = HashMap::new();
+    for (n, &(start, kind)) in starts.iter().enumerate() {
+        let end = starts.get(n + 1).map_or(tokens.len(), |next| next.0);
+        let attr_name = match &tokens[start] {
+            TokenTree::Ident(ident) => ident.clone(),
+            _ => unreachable!(),
+        };
+        let key = ATTRIBUTES
+            .iter()
+            .map(|attr| attr.0)
+            .find(|attr| attr_name.to_string() == *attr)
+            .unwrap();
+        if attrs.contains_key(key) {
+            return Err(Error::new(
+                attr_name.span(),
+                format!("duplicate attribute `{}`", key),
+            ));
+        }
+        // Skip the name and the colon
+        let value = check_value(&tokens[start + 2..end], kind, &attr_name)?;
+        attrs.insert(key, value);
+    }
+
+    Ok(ActorDef {
+        name,
+        generics,
+        attrs,
+    })
+}
+
+/// Returns kind of the attribute if a supported attribute starts at `tokens[i]`.
+fn attribute_at(tokens: &[TokenTree], i: usize) -> Option<Kind> {
+    let name = match &tokens[i] {
+        TokenTree::Ident(ident) => ident.to_string(),
+        _ => return None,
+    };
+    let kind = ATTRIBUTES.iter().find(|attr| attr.0 == name)?.1;
+    if !is_name_colon(tokens, i) || is_binding(tokens, i) {
+        return None;
+    }
+    Some(kind)
+}
+
+/// For each token, is it inside closure parameters (`|data: u32|`) or generic arguments
+/// (`Vec<T>`), where `name:` is not an attribute?
+fn nested(tokens: &[TokenTree]) -> Vec<bool> {
+    let mut nested = Vec::with_capacity(tokens.len());
+    let mut in_closure = false;
+    // After `name:` (other than an attribute's) or `->`, until the end of the type. The
+    // actor's name is followed by its generics.
+    let mut in_type = true;
+    let mut angle_depth = 0;
+    for i in 0..tokens.len() {
+        let prev = i.checked_sub(1).map(|prev| &tokens[prev]);
+        match &tokens[i] {
+            TokenTree::Punct(p) if p.as_char() == '|' => {
+                if in_closure {
+                    in_closure = false;
+                } else if opens_closure(tokens, i) {
+                    in_closure = true;
+                }
+                nested.push(true);
+                continue;
+            }
+            _ if in_closure => (),
+            // `Type<` in a type, `path::<` or `: <T as Trait>`, but not `a < b` or `A < b`
+            TokenTree::Punct(p) if p.as_char() == '<' => {
+                let generic = match prev {
+                    Some(TokenTree::Ident(_)) => in_type,
+                    Some(prev) => is_punct(prev, ':'),
+                    None => false,
+                };
+                if generic || angle_depth > 0 {
+                    angle_depth += 1;
+                }
+            }
+            // `->` and `=>` are not closing brackets
+            TokenTree::Punct(p)
+                if p.as_char() == '>'
+                    && angle_depth > 0
+                    && !prev.is_some_and(|prev| is_punct(prev, '-') || is_punct(prev, '=')) =>
+            {
+                angle_depth -= 1
+            }
+            TokenTree::Punct(p)
+                if p.as_char() == '>' && prev.is_some_and(|prev| is_punct(prev, '-')) =>
+            {
+                in_type = true
+            }
+            // Types can't contain these, they are always at the top level
+            TokenTree::Punct(p)
+                if p.as_char() == ';'
+                    || (p.as_char() == '>' && prev.is_some_and(|prev| is_punct(prev, '='))) =>
+            {
+                angle_depth = 0;
+                in_type = false;
+            }
+            TokenTree::Punct(p)
+                if angle_depth == 0 && (p.as_char() == ',' || p.as_char() == '=') =>
+            {
+                in_type = false
+            }
+            TokenTree::Group(_) if angle_depth == 0 => in_type = false,
+            TokenTree::Ident(_) if is_name_colon(tokens, i) => {
+                in_type = attribute_at(tokens, i).is_none() || angle_depth > 0
+            }
+            _ => (),
+        }
+        nested.push(in_closure || angle_depth > 0);
+    }
+    nested
+}
+
+/// Does `tokens[i]` (a `|`) start closure parameters, as opposed to being a binary
+/// operator, a pattern alternative or a part of `||`?
+fn opens_closure(tokens: &[TokenTree], i: usize) -> bool {
+    let prev = i.checked_sub(1).map(|prev| &tokens[prev]);
+    let joint_pipe = |token: Option<&TokenTree>| matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == '|' && p.spacing() == Spacing::Joint);
+    let is_double = joint_pipe(prev)
+        || (joint_pipe(tokens.get(i)) && tokens.get(i + 1).is_some_and(|t| is_punct(t, '|')));
+    if is_double {
+        return false;
+    }
+    match prev {
+        None | Some(TokenTree::Punct(_)) => true,
+        Some(TokenTree::Ident(ident)) => {
+            ident.to_string() == "move" || ident.to_string() == "return"
+        }
+        _ => false,
+    }
+}
+
+/// Is `tokens[i]` an identifier followed by a single colon (`name:`, but not `name::`)?
+fn is_name_colon(tokens: &[TokenTree], i: usize) -> bool {
+    if let TokenTree::Ident(_) = tokens[i] {
+    } else {
+        return false;
+    }
+    if i > 0 && is_punct(&tokens[i - 1], ':') {
+        // `path::name`
+        return false;
+    }
+    match tokens.get(i + 1) {
+        Some(TokenTree::Punct(p)) => p.as_char() == ':' && p.spacing() == Spacing::Alone,
+        _ => false,
+    }
+}
+
+/// Is `tokens[i]` a name declared by user code, e.g. `let data: u32` or `pub input: u32`?
+fn is_binding(tokens: &[TokenTree], i: usize) -> bool {
+    let keywords = ["let", "mut", "ref", "const", "static", "pub"];
+    match i.checked_sub(1).map(|prev| &tokens[prev]) {
+        // but not `T: 'static name:`
+        Some(TokenTree::Ident(ident)) => {
+            keywords.contains(&ident.to_string().as_str())
+                && !(i >= 2 && is_punct(&tokens[i - 2], '\''))
+        }
+        // `pub(crate) name: Type`
+        Some(TokenTree::Group(group)) => {
+            group.delimiter() == Delimiter::Parenthesis
+                && i >= 2
+                && tokens[i - 2].to_string() == "pub"
+        }
+        _ => false,
+    }
+}
+
+pub fn is_punct(token: &TokenTree, c: char) -> bool {
+    match token {
+        TokenTree::Punct(p) => p.as_char() == c,
+        _ => false,
+    }
+}
+
+/// Checks value of `#[actor]` argument, which is an `actor!` attribute of the same name.
+/// Returns the value like `check_value()`.
+pub fn check_argument(tokens: &[TokenTree], name: &Ident) -> Result<Vec<TokenTree>, Error> {
+    let kind = ATTRIBUTES
+        .iter()
+        .find(|attr| name.to_string() == attr.0)
+        .expect("`#[actor]` argument that is not an attribute")
+        .1;
+    check_value(tokens, kind, name)
+}
+
+/// Value of an integer literal, which may be hexadecimal, octal or binary and have
+/// underscores and an integer suffix (`1_000u64`). `None` for other literals.
+pub fn integer_value(literal: &Literal) -> Option<u128> {
+    let text = literal.to_string().replace('_', "");
+    let (radix, text) = match text.get(..2) {
+        Some("0x") => (16, &text[2..]),
+        Some("0o") => (8, &text[2..]),
+        Some("0b") => (2, &text[2..]),
+        _ => (10, text.as_str()),
+    };
+    let (digits, suffix) = text.split_at(text.find(['u', 'i']).unwrap_or(text.len()));
+    let integer_suffixes = [
+        "", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+    if !integer_suffixes.contains(&suffix) {
+        return None;
+    }
+    u128::from_str_radix(digits, radix).ok()
+}
+
+/// Checks attribute value. Returns the value with trailing comma removed, unless the
+/// comma is a part of it (as it is with match arms).
+fn check_value(tokens: &[TokenTree], kind: Kind, attr: &Ident) -> Result<Vec<TokenTree>, Error> {
+    let mut tokens = tokens.to_vec();
+    if kind != Kind::Arms && tokens.last().is_some_and(|t| is_punct(t, ',')) {
+        tokens.pop();
+    }
+    match kind {
+        Kind::Bool => {
+            check_single(&tokens, attr, "expected `true` or `false`", |token| {
+                let s = token.to_string();
+                s == "true" || s == "false"
+            })?;
+        }
+        Kind::Integer => {
+            check_single(&tokens, attr, "expected integer", |token| match token {
+                TokenTree::Literal(lit) => integer_value(lit).is_some(),
+                _ => false,
+            })?;
+            // The value can be used wherever an integer is expected
+            if let TokenTree::Literal(lit) = &tokens[0] {
+                let mut unsuffixed = Literal::u128_unsuffixed(integer_value(lit).unwrap());
+                unsuffixed.set_span(lit.span());
+                tokens[0] = unsuffixed.into();
+            }
+        }
+        Kind::Ident => {
+            check_single(&tokens, attr, "expected identifier", |token| {
+                matches!(token, TokenTree::Ident(_))
+            })?;
+        }
+        Kind::Docs => {
+            let mut i = 0;
+            while i < tokens.len() {
+                let is_attr = is_punct(&tokens[i], '#')
+                    && match tokens.get(i + 1) {
+                        Some(TokenTree::Group(g)) => g.delimiter() == Delimiter::Bracket,
+                        _ => false,
+                    };
+                if !is_attr {
+                    return Err(unexpected(&tokens, i, "expected doc comment"));
+                }
+                i += 2;
+            }
+        }
+        Kind::List => {
+            if let Some(i) = (0..tokens.len()).find(|&i| is_name_colon(&tokens, i)) {
+                return Err(unexpected(&tokens, i, "unexpected token"));
+            }
+        }
+        Kind::Fields => check_fields(&tokens)?,
+        Kind::InitializedFields => (),
+        Kind::Arms | Kind::Code => {
+            for i in 0..tokens.len() {
+                if !is_name_colon(&tokens, i) || is_binding(&tokens, i) {
+                    continue;
+                }
+                let statement_start = match i.checked_sub(1).map(|prev| &tokens[prev]) {
+                    None => true,
+                    Some(TokenTree::Punct(p)) => {
+                        p.as_char() == ';' || (kind == Kind::Arms && p.as_char() == ',')
+                    }
+                    Some(TokenTree::Group(g)) => g.delimiter() == Delimiter::Brace,
+                    _ => false,
+                };
+                if statement_start {
+                    return Err(unknown_attribute(&tokens[i]));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Checks that `tokens` is exactly one token accepted by `accept`.
+fn check_single(
+    tokens: &[TokenTree],
+    attr: &Ident,
+    expected: &str,
+    accept: impl Fn(&TokenTree) -> bool,
+) -> Result<(), Error> {
+    match tokens.first() {
+        None => Err(Error::new(
+            attr.span(),
+            format!("{} after `{}:`", expected, attr),
+        )),
+        Some(token) if !accept(token) => Err(Error::new(token.span(), expected)),
+        Some(_) if tokens.len() > 1 => Err(unexpected(tokens, 1, "unexpected token")),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Checks `name: Type` declarations separated by commas.
+fn check_fields(tokens: &[TokenTree]) -> Result<(), Error> {
+    let mut field_name = None;
+    let mut angle_depth = 0;
+    for i in 0..tokens.len() {
+        let token = &tokens[i];
+        if let Some(name) = field_name {
+            // Inside field type
+            match token {
+                TokenTree::Punct(p) if p.as_char() == '<' => angle_depth += 1,
+                // `->` is not a closing bracket
+                TokenTree::Punct(p)
+                    if p.as_char() == '>' && !(i > 0 && is_punct(&tokens[i - 1], '-')) =>
+                {
+                    angle_depth -= 1
+                }
+                TokenTree::Punct(p) if p.as_char() == ',' && angle_depth == 0 => field_name = None,
+                // Code does not belong here, the field is probably a misspelled attribute.
+                TokenTree::Punct(p) if p.as_char() == ';' => {
+                    return Err(unknown_attribute(&tokens[name]))
+                }
+                TokenTree::Ident(ident) if ident.to_string() == "let" => {
+                    return Err(unknown_attribute(&tokens[name]))
+                }
+                _ if angle_depth == 0 && is_name_colon(tokens, i) => {
+                    return Err(Error::new(token.span(), "expected `,` before next field"))
+                }
+                _ => (),
+            }
+        } else if is_name_colon(tokens, i) {
+            field_name = Some(i);
+        } else {
+            let visibility_or_attribute = match token {
+                TokenTree::Ident(ident) => {
+                    let s = ident.to_string();
+                    s == "pub" || s == "crate"
+                }
+                TokenTree::Punct(p) => p.as_char() == '#',
+                TokenTree::Group(g) => g.delimiter() != Delimiter::Brace,
+                _ => false,
+            };
+            if !visibility_or_attribute {
+                return Err(unexpected(
+                    tokens,
+                    i,
+                    "expected field, e.g. `pub name: Type,`",
+                ));
+            }
+        }
+    }
+    match field_name {
+        Some(i) if i + 2 == tokens.len() => Err(Error::new(
+            tokens[i + 1].span(),
+            "expected field type after `:`",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Error for `tokens[i]`, which should not be there. Misspelled attributes get a more
+/// helpful message.
+fn unexpected(tokens: &[TokenTree], i: usize, message: &str) -> Error {
+    if is_name_colon(tokens, i) {
+        unknown_attribute(&tokens[i])
+    } else {
+        Error::new(tokens[i].span(), message)
+    }
+}
+
+fn unknown_attribute(name: &TokenTree) -> Error {
+    let name_str = name.to_string();
+    let closest = ATTRIBUTES
+        .iter()
+        .map(|attr| (edit_distance(attr.0, &name_str), attr.0))
+        .min()
+        .filter(|(distance, _)| *distance <= 3);
+    let message = match closest {
+        Some((_, attr)) => format!("unknown attribute `{}`, did you mean `{}`?", name_str, attr),
+        None => format!("unknown attribute `{}`", name_str),
+    };
+    Error::new(name.span(), message)
+}
+
+/// Levenshtein distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for j in 0..b.len() {
+            let substitution = prev + if ca == b[j] { 0 } else { 1 };
+            prev = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    row[b.len()]
+}
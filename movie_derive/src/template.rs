@@ -0,0 +1,42 @@
+//! Code generation from templates.
+//!
+//! A template is Rust code with `#name` placeholders (like in `quote!`), which are
+//! replaced with token streams. Unlike `format!`-ing strings, user code keeps its spans,
+//! so compiler errors inside it point at the actor definition.
+
+use proc_macro::{Group, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+
+pub fn expand(template: &str, vars: &HashMap<&str, TokenStream>) -> TokenStream {
+    substitute(template.parse().expect("invalid template"), vars)
+}
+
+fn substitute(stream: TokenStream, vars: &HashMap<&str, TokenStream>) -> TokenStream {
+    let mut output = Vec::new();
+    let mut tokens = stream.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(ref p) if p.as_char() == '#' => {
+                let value = match tokens.peek() {
+                    Some(TokenTree::Ident(ident)) => vars.get(ident.to_string().as_str()),
+                    _ => None,
+                };
+                match value {
+                    Some(value) => {
+                        output.extend(value.clone());
+                        tokens.next();
+                    }
+                    None => output.push(token),
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(group.delimiter(), substitute(group.stream(), vars));
+                new_group.set_span(group.span());
+                output.push(new_group.into());
+            }
+            other => output.push(other),
+        }
+    }
+    output.into_iter().collect()
+}
@@ -0,0 +1,113 @@
+//! Parsing and expansion of `timers` attribute.
+//!
+//! Timers are written like match arms: `name(interval) => handler,`. The interval is
+//! either an integer literal (milliseconds, with or without a suffix) or an expression of
+//! type `Duration`.
+
+use crate::parse::{integer_value, is_punct, Error};
+use crate::template;
+use proc_macro::{Delimiter, Literal, TokenStream, TokenTree};
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Returns code starting the timers (for `on_init`) and match arms handling them (for
+/// `match timer`, where `timer` is the name).
+pub fn expand(input: TokenStream) -> Result<(TokenStream, TokenStream), Error> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut starts = TokenStream::new();
+    let mut arms = TokenStream::new();
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let name = match &tokens[i] {
+            TokenTree::Ident(name) => name.clone(),
+            other => {
+                return Err(Error::new(
+                    other.span(),
+                    "expected timer, e.g. `report(1000) => ...`",
+                ))
+            }
+        };
+        if names.contains(&name.to_string()) {
+            return Err(Error::new(
+                name.span(),
+                format!("duplicate timer `{}`", name),
+            ));
+        }
+        names.push(name.to_string());
+        let interval = match tokens.get(i + 1) {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                group.stream()
+            }
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    format!("expected interval, e.g. `{}(1000)`", name),
+                ))
+            }
+        };
+        let arrow = match (tokens.get(i + 2), tokens.get(i + 3)) {
+            (Some(eq), Some(gt)) if is_punct(eq, '=') && is_punct(gt, '>') => i + 2,
+            _ => return Err(Error::new(tokens[i + 1].span(), "expected `=>`")),
+        };
+        // The handler is a block, or an expression ending with a comma
+        let start = arrow + 2;
+        let end = match tokens.get(start) {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => start + 1,
+            _ => (start..tokens.len())
+                .find(|&i| is_punct(&tokens[i], ','))
+                .unwrap_or(tokens.len()),
+        };
+        if start == end {
+            return Err(Error::new(tokens[arrow + 1].span(), "expected handler"));
+        }
+
+        let mut vars = HashMap::new();
+        vars.insert(
+            "name",
+            TokenTree::from(Literal::string(&name.to_string())).into(),
+        );
+        vars.insert("interval", millis_or_duration(interval)?);
+        vars.insert("handler", tokens[start..end].iter().cloned().collect());
+        starts.extend(template::expand(
+            "ctx.start_timer(#name, #interval);",
+            &vars,
+        ));
+        arms.extend(template::expand("#name => #handler,", &vars));
+
+        i = end;
+        if tokens.get(i).is_some_and(|token| is_punct(token, ',')) {
+            i += 1;
+        }
+    }
+    Ok((starts, arms))
+}
+
+/// `Duration::from_millis()` of an integer literal, which may have a suffix (`1000u64`), or
+/// the expression itself. Other literals are rejected.
+fn millis_or_duration(interval: TokenStream) -> Result<TokenStream, Error> {
+    let tokens: Vec<TokenTree> = interval.clone().into_iter().collect();
+    let literal = match tokens.as_slice() {
+        [TokenTree::Literal(literal)] => literal,
+        _ => return Ok(interval),
+    };
+    let millis = match integer_value(literal).and_then(|millis| u64::try_from(millis).ok()) {
+        Some(millis) => millis,
+        None => {
+            return Err(Error::new(
+                literal.span(),
+                "expected milliseconds (e.g. `1000`) or a `Duration` expression",
+            ))
+        }
+    };
+    // Unsuffixed, as `from_millis()` takes `u64`
+    let mut millis = Literal::u64_unsuffixed(millis);
+    millis.set_span(literal.span());
+    let mut vars = HashMap::new();
+    vars.insert("millis", TokenTree::from(millis).into());
+    Ok(template::expand(
+        "std::time::Duration::from_millis(#millis)",
+        &vars,
+    ))
+}
@@ -21,45 +21,75 @@
 //!
 //! ```rust,ignore
 //! /// This is an example actor.
+//! #[allow(non_snake_case)]
 //! pub mod SomeActor {
 //!     use super::*;
 //!     pub struct Actor {}
 //!     pub enum Input {
 //!         Ping,
 //!     }
-//!     pub type Handle = movie::Handle<std::thread::JoinHandle<()>, Input>;
+//!     /// Methods sending each variant of `Input`, e.g. `handle.change_source(name)`
+//!     /// for `ChangeSource(String)`. Implemented for `Addr`, and so usable on `Handle`.
+//!     pub trait HandleExt {
+//!         fn ping(&self) -> Result<(), movie::SendError<Input>>;
+//!     }
+//!     impl HandleExt for movie::Addr<Input> {
+//!         fn ping(&self) -> Result<(), movie::SendError<Input>> {
+//!             self.send(Input::Ping)
+//!         }
+//!     }
+//!     pub type Handle =
+//!         movie::Handle<std::thread::JoinHandle<<Actor as movie::Actor>::Output>, Input>;
 //!     impl Actor {
-//!         pub fn start(mut self) -> Handle {
-//!             let (tx_ota, rx_ota) = std::sync::mpsc::channel();
-//!             let (tx_kill, rx_kill) = std::sync::mpsc::channel();
-//!             let handle = std::thread::spawn(move || {
-//!                 {}; // on_init
-//!                 let mut running = true;
-//!                 while running {
-//!                     while let Ok(message) = rx_ota.try_recv() {
-//!                         use Input::*;
-//!                         match message {
-//!                             Ping => (), //on_message
-//!                         };
-//!                     }
-//!                     if let Ok(_) = rx_kill.try_recv() {
-//!                         running = false;
-//!                         {}; // on_stop
-//!                     }
-//!                     {}; // on_tick
-//!                     use std::thread::sleep;
-//!                     use std::time::Duration;
-//!                     sleep(Duration::from_millis(100));
-//!                 }
-//!             });
-//!             movie::Handle {
-//!                 join_handle: handle,
-//!                 tx: tx_ota,
-//!                 kill: tx_kill,
+//!         pub fn start(self) -> Handle {
+//!             movie::start(self)
+//!         }
+//!         pub fn try_start(self) -> std::io::Result<Handle> {
+//!             movie::try_start(self)
+//!         }
+//!         pub fn run_here(self) -> <Self as movie::Actor>::Output {
+//!             movie::run_here(self)
+//!         }
+//!         pub fn run_with_handle(
+//!             self,
+//!             f: impl FnOnce(
+//!                 movie::Handle<
+//!                     movie::local::LocalJoinHandle<<Self as movie::Actor>::Output>,
+//!                     <Self as movie::Actor>::Input,
+//!                 >,
+//!             ),
+//!         ) {
+//!             movie::run_with_handle(self, f)
+//!         }
+//!     }
+//!     #[allow(unused_variables, unused_mut)]
+//!     impl movie::Actor for Actor {
+//!         type Input = Input;
+//!         type Output = ();
+//!         const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+//!         const TICK_SCHEDULE: movie::TickSchedule = movie::TickSchedule::FixedDelay;
+//!         const STACK_SIZE: Option<usize> = None;
+//!         fn name() -> &'static str {
+//!             "SomeActor"
+//!         }
+//!         fn mailbox() -> (movie::Addr<Self::Input>, movie::Mailbox<Self::Input>) {
+//!             movie::mailbox()
+//!         }
+//!         fn on_init(&mut self, ctx: &mut movie::Context<Self::Input>) {}
+//!         fn on_message(&mut self, ctx: &mut movie::Context<Self::Input>, message: Self::Input) {
+//!             use Input::*;
+//!             match message {
+//!                 Ping => (),
 //!             }
 //!         }
+//!         fn on_tick(&mut self, ctx: &mut movie::Context<Self::Input>) {}
+//!         fn on_stop(mut self, ctx: &mut movie::Context<Self::Input>) -> () {
+//!             (|| {})();
+//!         }
 //!     }
 //! }
+//!
+//! // The actor loop itself is `movie::Actor::run()`.
 //! ```
 //!
 //! [`cargo-expand`]: https://github.com/dtolnay/cargo-expand
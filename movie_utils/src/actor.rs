@@ -0,0 +1,301 @@
+//! `Actor` trait, implemented by `actor!` and `#[actor]`, or by hand.
+//!
+//! ```rust,ignore
+//! use movie::{Actor, Context};
+//!
+//! struct Counter {
+//!     count: u32,
+//! }
+//!
+//! enum CounterInput {
+//!     Increment,
+//!     Get(movie::ReplyTo<u32>),
+//! }
+//!
+//! impl Actor for Counter {
+//!     type Input = CounterInput;
+//!     type Output = u32;
+//!     fn on_message(&mut self, _ctx: &mut Context<CounterInput>, message: CounterInput) {
+//!         match message {
+//!             CounterInput::Increment => self.count += 1,
+//!             CounterInput::Get(reply) => reply.send(self.count),
+//!         }
+//!     }
+//!     fn on_stop(self, _ctx: &mut Context<CounterInput>) -> u32 {
+//!         self.count
+//!     }
+//! }
+//!
+//! let counter = movie::start(Counter { count: 0 });
+//! ```
+
+use crate::context::{Context, TickSchedule};
+use crate::mailbox::{mailbox, Addr, Envelope, Mailbox};
+use crate::{Handle, JoinableHandle};
+
+use std::io;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// An actor: its data and how it handles messages.
+pub trait Actor: Sized {
+    /// Messages the actor handles.
+    type Input;
+    /// Value returned by [`on_stop()`], and then by `Handle::stop()`.
+    ///
+    /// [`on_stop()`]: #tymethod.on_stop
+    type Output;
+
+    /// Time between ticks.
+    const TICK_INTERVAL: Duration = Duration::from_millis(100);
+    /// How ticks are scheduled.
+    const TICK_SCHEDULE: TickSchedule = TickSchedule::FixedDelay;
+    /// Stack size of the actor's thread in bytes, see [`start()`]. By default, Rust's
+    /// default (see `std::thread::Builder::stack_size()`).
+    ///
+    /// [`start()`]: fn.start.html
+    const STACK_SIZE: Option<usize> = None;
+    /// The handlers only work when called by [`run()`], e.g. because `run()` is overridden
+    /// (`actor!` does it when `on_init` declares variables). `Pump`, `ThreadPool` and
+    /// `TestActor` call the handlers one by one, so they reject such actors at compile
+    /// time.
+    ///
+    /// [`run()`]: #method.run
+    const RUN_ONLY: bool = false;
+    /// The handlers must all run on the same thread, e.g. because the actor keeps its
+    /// state in a thread-local (`actor!` does it for `local_data`). `ThreadPool` moves
+    /// actors between threads, so it rejects such actors at compile time.
+    const THREAD_BOUND: bool = false;
+
+    /// Name of the actor, see `Context::name()`. By default, name of the type.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+    /// Name of the actor's thread, see [`start()`]. By default, [`name()`]; actors with
+    /// many instances can add an id to it.
+    ///
+    /// [`start()`]: fn.start.html
+    /// [`name()`]: #method.name
+    fn thread_name(&self) -> String {
+        Self::name().to_string()
+    }
+    /// Creates the actor's mailbox. By default, it's unbounded.
+    fn mailbox() -> (Addr<Self::Input>, Mailbox<Self::Input>) {
+        mailbox()
+    }
+
+    /// Runs just before the actor starts accepting messages.
+    fn on_init(&mut self, _ctx: &mut Context<Self::Input>) {}
+    /// Handles a message.
+    fn on_message(&mut self, ctx: &mut Context<Self::Input>, message: Self::Input);
+    /// Runs every [`TICK_INTERVAL`], or as often as set with `ctx.set_tick_interval()`.
+    ///
+    /// [`TICK_INTERVAL`]: #associatedconstant.TICK_INTERVAL
+    fn on_tick(&mut self, _ctx: &mut Context<Self::Input>) {}
+    /// Runs when a timer started with `ctx.start_timer()` fires.
+    fn on_timer(&mut self, _ctx: &mut Context<Self::Input>, _timer: &'static str) {}
+    /// Runs just after the actor stops accepting messages.
+    fn on_stop(self, ctx: &mut Context<Self::Input>) -> Self::Output;
+
+    /// Runs the actor: `on_init`, then [`run_loop()`], then `on_stop`.
+    ///
+    /// Called by [`start()`] in the spawned thread. There should be no need to
+    /// override it.
+    ///
+    /// [`run_loop()`]: fn.run_loop.html
+    /// [`start()`]: fn.start.html
+    fn run(mut self, mut ctx: Context<Self::Input>, mailbox: Mailbox<Self::Input>) -> Self::Output {
+        ctx.set_tick_interval(Self::TICK_INTERVAL);
+        ctx.set_tick_schedule(Self::TICK_SCHEDULE);
+        self.on_init(&mut ctx);
+        run_loop(&mut ctx, &mailbox, |ctx, event| match event {
+            Event::Message(message) => self.on_message(ctx, message),
+            Event::Tick => self.on_tick(ctx),
+            Event::Timer(timer) => self.on_timer(ctx, timer),
+        });
+        // `mailbox` is dropped after `on_stop`, so the actor is alive until it returns
+        self.on_stop(&mut ctx)
+    }
+}
+
+/// Something for the actor to handle, see [`run_loop()`].
+///
+/// [`run_loop()`]: fn.run_loop.html
+pub enum Event<TX> {
+    Message(TX),
+    Tick,
+    /// A timer with this name fired.
+    Timer(&'static str),
+}
+
+/// The actor loop. Waits for messages until it's time to tick or fire a timer, and
+/// stops after a stop request or `ctx.stop()`. Messages have priority over ticks, ticks
+/// over timers.
+pub fn run_loop<TX>(
+    ctx: &mut Context<TX>,
+    mailbox: &Mailbox<TX>,
+    mut handle: impl FnMut(&mut Context<TX>, Event<TX>),
+) {
+    while !ctx.is_stopping() {
+        let timeout = next_deadline(ctx).saturating_duration_since(Instant::now());
+        let envelope = mailbox.recv_timeout(timeout).ok();
+        run_round(ctx, envelope, &mut handle);
+    }
+}
+
+/// What [`run_step()`] left the actor doing.
+pub(crate) enum Step {
+    /// It has more messages, or a tick or a timer that is due.
+    Busy,
+    /// It has nothing to do until this time, or until a message arrives.
+    Idle(Instant),
+    /// It's stopping, `on_stop` should run.
+    Stopping,
+}
+
+/// Like [`run_loop()`], but never waits: handles at most `budget` messages, and the
+/// ticks and timers that are due.
+pub(crate) fn run_step<TX>(
+    ctx: &mut Context<TX>,
+    mailbox: &Mailbox<TX>,
+    budget: usize,
+    mut handle: impl FnMut(&mut Context<TX>, Event<TX>),
+) -> Step {
+    for _ in 0..budget {
+        if ctx.is_stopping() {
+            return Step::Stopping;
+        }
+        let envelope = mailbox.recv_timeout(Duration::from_secs(0)).ok();
+        if envelope.is_none() {
+            let deadline = next_deadline(ctx);
+            if Instant::now() < deadline {
+                return Step::Idle(deadline);
+            }
+        }
+        run_round(ctx, envelope, &mut handle);
+    }
+    if ctx.is_stopping() {
+        Step::Stopping
+    } else {
+        Step::Busy
+    }
+}
+
+/// When the next tick or timer is due.
+pub(crate) fn next_deadline<TX>(ctx: &Context<TX>) -> Instant {
+    match ctx.timers.next_deadline() {
+        Some(timer) => timer.min(ctx.next_tick),
+        None => ctx.next_tick,
+    }
+}
+
+/// Handles the message or stop request, if any, then the tick and timers that are due.
+pub(crate) fn run_round<TX>(
+    ctx: &mut Context<TX>,
+    envelope: Option<Envelope<TX>>,
+    handle: &mut impl FnMut(&mut Context<TX>, Event<TX>),
+) {
+    match envelope {
+        Some(Envelope::Message(message)) => handle(ctx, Event::Message(message)),
+        Some(Envelope::Stop) => ctx.stop(),
+        None => (),
+    }
+    let now = ctx.now();
+    if !ctx.is_stopping() && now >= ctx.next_tick {
+        ctx.start_tick(now);
+        handle(ctx, Event::Tick);
+        ctx.end_tick();
+    }
+    while !ctx.is_stopping() {
+        let timer = match ctx.timers.take_due(now) {
+            Some(timer) => timer,
+            None => break,
+        };
+        handle(ctx, Event::Timer(timer));
+        let now = ctx.now();
+        ctx.timers.fired(timer, now);
+    }
+}
+
+/// Spawns the actor in a new thread, named [`Actor::thread_name()`] and with
+/// [`Actor::STACK_SIZE`].
+///
+/// # Panics
+///
+/// If the thread can't be spawned, like `std::thread::spawn()`. See [`try_start()`].
+///
+/// [`Actor::thread_name()`]: trait.Actor.html#method.thread_name
+/// [`Actor::STACK_SIZE`]: trait.Actor.html#associatedconstant.STACK_SIZE
+/// [`try_start()`]: fn.try_start.html
+pub fn start<A>(actor: A) -> Handle<JoinHandle<A::Output>, A::Input>
+where
+    A: Actor + Send + 'static,
+    A::Input: Send + 'static,
+    A::Output: Send + 'static,
+{
+    try_start(actor).expect("failed to spawn thread")
+}
+
+/// Like [`start()`], but returns the error if the thread can't be spawned, e.g. when the
+/// system is out of memory or threads.
+///
+/// [`start()`]: fn.start.html
+pub fn try_start<A>(actor: A) -> io::Result<Handle<JoinHandle<A::Output>, A::Input>>
+where
+    A: Actor + Send + 'static,
+    A::Input: Send + 'static,
+    A::Output: Send + 'static,
+{
+    let builder = thread_builder(&actor);
+    let (addr, mailbox) = A::mailbox();
+    let ctx = Context::new(A::name(), addr.clone());
+    let join_handle = builder.spawn(move || actor.run(ctx, mailbox))?;
+    Ok(Handle { join_handle, addr })
+}
+
+/// Builder of the actor's thread, see [`start()`].
+///
+/// [`start()`]: fn.start.html
+pub(crate) fn thread_builder<A: Actor>(actor: &A) -> thread::Builder {
+    let builder = thread::Builder::new().name(actor.thread_name());
+    match A::STACK_SIZE {
+        Some(size) => builder.stack_size(size),
+        None => builder,
+    }
+}
+
+/// Spawns the actor with `spawner`, e.g. `std::thread::spawn` or any function with a
+/// similar signature.
+///
+/// The actor only has to live as long as the spawner allows, so actors borrowing data
+/// can be started in a scope: `movie::start_with(actor, |run| scope.spawn(run))`.
+pub fn start_with<'a, A, S, T>(actor: A, spawner: S) -> Handle<T, A::Input>
+where
+    A: Actor + Send + 'a,
+    A::Input: Send + 'a,
+    A::Output: Send + 'a,
+    S: FnOnce(Box<dyn FnOnce() -> A::Output + Send + 'a>) -> T,
+    T: JoinableHandle,
+{
+    let (addr, mailbox) = A::mailbox();
+    spawn(actor, addr, mailbox, spawner)
+}
+
+/// Spawns the actor with a mailbox created beforehand.
+pub(crate) fn spawn<'a, A, S, T>(
+    actor: A,
+    addr: Addr<A::Input>,
+    mailbox: Mailbox<A::Input>,
+    spawner: S,
+) -> Handle<T, A::Input>
+where
+    A: Actor + Send + 'a,
+    A::Input: Send + 'a,
+    A::Output: Send + 'a,
+    S: FnOnce(Box<dyn FnOnce() -> A::Output + Send + 'a>) -> T,
+    T: JoinableHandle,
+{
+    let ctx = Context::new(A::name(), addr.clone());
+    let join_handle = spawner(Box::new(move || actor.run(ctx, mailbox)));
+    Handle { join_handle, addr }
+}
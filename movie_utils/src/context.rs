@@ -0,0 +1,185 @@
+//! State of a running actor, available as `ctx` inside its code.
+
+use crate::mailbox::Addr;
+use crate::subscribers::HasOutput;
+use crate::timer::Timers;
+
+use std::time::{Duration, Instant};
+
+/// Actor's view of itself. Available as `ctx` in `on_init`, `on_message`, `on_tick`,
+/// timer handlers and `on_stop`.
+pub struct Context<TX> {
+    name: &'static str,
+    addr: Addr<TX>,
+    tick_count: u64,
+    tick_interval: Duration,
+    tick_schedule: TickSchedule,
+    missed_ticks: u64,
+    pub(crate) next_tick: Instant,
+    pub(crate) timers: Timers,
+    stopping: bool,
+    /// Time of the virtual clock, if the actor runs on one (see `TestActor`).
+    pub(crate) virtual_now: Option<Instant>,
+}
+
+impl<TX> Context<TX> {
+    /// Context of an actor that is about to start. Ticks every 100ms until the interval
+    /// is changed with `set_tick_interval()`.
+    pub(crate) fn new(name: &'static str, addr: Addr<TX>) -> Self {
+        Context {
+            name,
+            addr,
+            tick_count: 0,
+            tick_interval: Duration::from_millis(100),
+            tick_schedule: TickSchedule::FixedDelay,
+            missed_ticks: 0,
+            next_tick: Instant::now() + Duration::from_millis(100),
+            timers: Timers::default(),
+            stopping: false,
+            virtual_now: None,
+        }
+    }
+    /// Name of the actor, e.g. `StreamParsingActor`.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+    /// Address of the actor itself. Can be cloned and given to other actors.
+    pub fn addr(&self) -> &Addr<TX> {
+        &self.addr
+    }
+    /// Current time, `Instant::now()` unless the actor runs on a virtual clock in a
+    /// [`TestActor`]. Actors that should be testable without waiting should use it instead
+    /// of `Instant::now()`.
+    ///
+    /// [`TestActor`]: ../testing/struct.TestActor.html
+    pub fn now(&self) -> Instant {
+        self.virtual_now.unwrap_or_else(Instant::now)
+    }
+    /// Number of ticks so far, including the current one (inside `on_tick`).
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+    /// Time between ticks, `tick_interval` unless changed.
+    pub fn tick_interval(&self) -> Duration {
+        self.tick_interval
+    }
+    /// Changes time between ticks. The next tick is `interval` from now.
+    pub fn set_tick_interval(&mut self, interval: Duration) {
+        self.tick_interval = interval;
+        self.next_tick = self.now() + interval;
+    }
+    /// How ticks are scheduled, `tick_schedule` unless changed.
+    pub fn tick_schedule(&self) -> TickSchedule {
+        self.tick_schedule
+    }
+    /// Changes how ticks are scheduled. With a fixed rate, ticks are due every
+    /// `tick_interval` from the next one.
+    pub fn set_tick_schedule(&mut self, schedule: TickSchedule) {
+        self.tick_schedule = schedule;
+    }
+    /// Inside `on_tick`, number of ticks the current one is late by: ticks skipped just
+    /// before it with `TickSchedule::Skip`, ticks still to catch up on after it with
+    /// `TickSchedule::CatchUp`. Always 0 with `TickSchedule::FixedDelay`.
+    pub fn missed_ticks(&self) -> u64 {
+        self.missed_ticks
+    }
+    /// Starts a timer, calling the actor's timer handler with `name` every `interval`.
+    /// A timer with the same name is replaced.
+    pub fn start_timer(&mut self, name: &'static str, interval: Duration) {
+        let now = self.now();
+        self.timers.start(name, interval, now);
+    }
+    /// Changes the timer's interval. It next fires `interval` from now (or from resuming,
+    /// if it's paused). Returns `false` if there's no such timer.
+    pub fn set_timer_interval(&mut self, name: &'static str, interval: Duration) -> bool {
+        let now = self.now();
+        self.timers.set_interval(name, interval, now)
+    }
+    /// Pauses the timer, remembering how much time was left. Returns `false` if there's
+    /// no such timer.
+    pub fn pause_timer(&mut self, name: &'static str) -> bool {
+        let now = self.now();
+        self.timers.pause(name, now)
+    }
+    /// Resumes a paused timer. Returns `false` if there's no such timer.
+    pub fn resume_timer(&mut self, name: &'static str) -> bool {
+        let now = self.now();
+        self.timers.resume(name, now)
+    }
+    /// Removes the timer. Returns `false` if there's no such timer.
+    pub fn cancel_timer(&mut self, name: &'static str) -> bool {
+        self.timers.cancel(name)
+    }
+    /// Interval of the timer, `None` if there's no such timer.
+    pub fn timer_interval(&self, name: &str) -> Option<Duration> {
+        self.timers.interval(name)
+    }
+    /// Returns `true` if the timer exists and is not paused.
+    pub fn is_timer_running(&self, name: &str) -> bool {
+        self.timers.is_running(name)
+    }
+    /// Stops the actor after the current handler returns. `on_stop` still runs, messages
+    /// that have not been handled yet are dropped.
+    pub fn stop(&mut self) {
+        self.stopping = true;
+    }
+    /// Returns `true` if the actor is going to stop, after [`stop()`] or a stop request
+    /// from `Handle`.
+    ///
+    /// [`stop()`]: #method.stop
+    pub fn is_stopping(&self) -> bool {
+        self.stopping
+    }
+    /// Used by the actor loop before `on_tick`, when the tick is due at `now`.
+    pub(crate) fn start_tick(&mut self, now: Instant) {
+        self.tick_count += 1;
+        let interval = self.tick_interval.as_nanos();
+        let late = now.saturating_duration_since(self.next_tick).as_nanos();
+        self.missed_ticks = match self.tick_schedule {
+            TickSchedule::FixedDelay => 0,
+            _ if interval == 0 => 0,
+            _ => (late / interval) as u64,
+        };
+        // With a fixed rate, the next tick doesn't depend on how long this one takes
+        let ticks = match self.tick_schedule {
+            TickSchedule::FixedDelay => return,
+            TickSchedule::CatchUp => 1,
+            TickSchedule::Skip => self.missed_ticks + 1,
+        };
+        self.next_tick += Duration::from_nanos((interval * u128::from(ticks)) as u64);
+    }
+    /// Used by the actor loop after `on_tick`.
+    pub(crate) fn end_tick(&mut self) {
+        if let TickSchedule::FixedDelay = self.tick_schedule {
+            self.next_tick = self.now() + self.tick_interval;
+        }
+    }
+}
+
+impl<TX> Context<TX>
+where
+    TX: HasOutput,
+    TX::Output: Clone + Send + 'static,
+{
+    /// Sends `event` to everyone who subscribed with [`Addr::subscribe()`]. Does nothing
+    /// if there are no subscribers.
+    ///
+    /// [`Addr::subscribe()`]: ../mailbox/struct.Addr.html#method.subscribe
+    pub fn emit(&self, event: TX::Output) {
+        self.addr.emit(event);
+    }
+}
+
+/// How ticks are scheduled, see `Actor::TICK_SCHEDULE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickSchedule {
+    /// A tick is due `tick_interval` after the previous `on_tick` returned, so time spent
+    /// in handlers makes the period longer. The default.
+    FixedDelay,
+    /// Ticks are due every `tick_interval`. Late ticks run back to back until the actor
+    /// catches up.
+    CatchUp,
+    /// Ticks are due every `tick_interval`. Ticks late by a whole interval or more are
+    /// skipped, and `ctx.missed_ticks()` says how many.
+    Skip,
+}
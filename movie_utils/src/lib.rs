@@ -1,47 +1,209 @@
 #![doc(html_root_url = "https://movie.pzmarzly.pl")]
 
-//! `movie_utils` - crate containing `Handle` type and `JoinableHandle` trait.
+//! `movie_utils` - crate containing `Handle` and `Addr` types, `JoinableHandle` trait and
+//! utilities for managing groups of actors.
 
-use std::thread::JoinHandle;
+use std::any::Any;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Condvar, Mutex};
+use std::thread::{JoinHandle, ScopedJoinHandle};
+use std::time::Duration;
+
+pub mod actor;
+pub mod context;
+pub mod local;
+#[doc(hidden)]
+pub mod local_data;
+pub mod mailbox;
+pub mod pipeline;
+pub mod pool;
+pub mod reply;
+pub mod scheduler;
+mod subscribers;
+pub mod supervisor;
+pub mod system;
+pub mod testing;
+mod timer;
+pub use actor::{start, start_with, try_start, Actor};
+pub use context::{Context, TickSchedule};
+pub use local::{run_here, run_with_handle, Pump, Pumped};
+pub use mailbox::{bounded_mailbox, mailbox, Addr, Envelope, Mailbox, Overflow, SendError};
+pub use pipeline::{Pipeline, PipelineError};
+pub use pool::ThreadPool;
+pub use reply::{AskError, Pending, ReplyTo};
+pub use scheduler::Scheduled;
+pub use subscribers::HasOutput;
+pub use system::{ActorSystem, ShutdownReport};
+pub use testing::TestActor;
 
 /// Trait for `join()` method that allow to to wait on actor.
 /// Implemented for [`std::thread::JoinHandle`].
 ///
 /// [`std::thread::JoinHandle`]: https://doc.rust-lang.org/stable/std/thread/struct.JoinHandle.html
 pub trait JoinableHandle {
-    fn join(self);
+    /// Value returned by the actor when it stops (`()` unless `returns` is set).
+    type Output;
+    /// Waits for the actor to finish. Returns `Err` with the panic payload if the actor
+    /// panicked.
+    fn join(self) -> std::thread::Result<Self::Output>;
 }
 
-impl JoinableHandle for JoinHandle<()> {
-    #[allow(unused_must_use)]
-    fn join(self) {
-        self.join();
+impl<T> JoinableHandle for JoinHandle<T> {
+    type Output = T;
+    fn join(self) -> std::thread::Result<T> {
+        JoinHandle::join(self)
+    }
+}
+
+/// Actors borrowing data from the scope, started with
+/// `movie::start_with(actor, |run| scope.spawn(run))`.
+impl<T> JoinableHandle for ScopedJoinHandle<'_, T> {
+    type Output = T;
+    fn join(self) -> std::thread::Result<T> {
+        ScopedJoinHandle::join(self)
+    }
+}
+
+/// Where an actor without a thread of its own puts its result, for `join()`.
+pub(crate) struct Slot<T> {
+    result: Mutex<Option<std::thread::Result<T>>>,
+    done: Condvar,
+}
+
+impl<T> Slot<T> {
+    pub fn new() -> Self {
+        Slot {
+            result: Mutex::new(None),
+            done: Condvar::new(),
+        }
+    }
+    pub fn set(&self, result: std::thread::Result<T>) {
+        *self.result.lock().unwrap() = Some(result);
+        self.done.notify_all();
+    }
+    /// Waits for the result. Can only be called once.
+    pub fn wait(&self) -> std::thread::Result<T> {
+        let result = self.result.lock().unwrap();
+        let mut result = self
+            .done
+            .wait_while(result, |result| result.is_none())
+            .unwrap();
+        result.take().unwrap()
     }
 }
 
 /// Handle returned by `Actor::start()`. Generic version.
+///
+/// Owns the actor: only the handle can stop it. Other threads and actors should get
+/// an [`Addr`] through [`addr()`]. All methods of [`Addr`] (`send`, `ask`, etc.) can be
+/// called on the handle directly.
+///
+/// [`Addr`]: mailbox/struct.Addr.html
+/// [`addr()`]: #method.addr
 pub struct Handle<T: JoinableHandle, TX> {
     /// The underlying handle to process, thread, task, future, etc.
     pub join_handle: T,
-    /// Sender of channel used to send messages to an actor.
-    pub tx: std::sync::mpsc::Sender<TX>,
-    /// Sender of channel used to ask an actor to stop.
+    /// Address used to send messages to an actor.
+    pub addr: Addr<TX>,
+}
+
+impl<T: JoinableHandle, TX> Handle<T, TX> {
+    /// Returns a cloneable address of the actor.
+    pub fn addr(&self) -> Addr<TX> {
+        self.addr.clone()
+    }
+    /// Asks the actor to stop, without waiting for it. Messages sent before are handled
+    /// first.
+    pub fn request_stop(&self) {
+        self.addr.send_stop();
+    }
+    /// Asks the actor to stop and waits (blocking) for it to stop.
     ///
-    /// `kill` is used internally, use [`stop()`] instead.
+    /// Messages sent before are handled first. Returns the value returned by `on_stop`
+    /// (see `returns` attribute), or `Err` with the panic payload if the actor panicked
+    /// (before or while stopping).
+    pub fn stop(self) -> std::thread::Result<T::Output> {
+        // If the actor is not running anymore, join will tell why
+        self.request_stop();
+        self.join_handle.join()
+    }
+    /// Waits (blocking) for the actor to stop by itself (e.g. with `ctx.stop()`), without
+    /// asking it to. Returns the same as [`stop()`].
     ///
     /// [`stop()`]: #method.stop
-    pub kill: std::sync::mpsc::Sender<()>,
+    pub fn join(self) -> std::thread::Result<T::Output> {
+        self.join_handle.join()
+    }
+    /// Like [`stop()`], but gives up waiting after `timeout`, returning the handle back
+    /// in [`StopOutcome::TimedOut`].
+    ///
+    /// [`stop()`]: #method.stop
+    /// [`StopOutcome::TimedOut`]: enum.StopOutcome.html#variant.TimedOut
+    pub fn stop_timeout(self, timeout: Duration) -> StopOutcome<Self, T::Output> {
+        self.request_stop();
+        // The actor drops its mailbox when it finishes, so this works for any spawner.
+        if !self.addr.wait_closed(timeout) {
+            return StopOutcome::TimedOut(self);
+        }
+        match self.join_handle.join() {
+            Ok(output) => StopOutcome::Stopped(output),
+            Err(payload) => StopOutcome::Panicked(payload),
+        }
+    }
+    /// Returns `true` if the actor is no longer running (it stopped or panicked).
+    pub fn is_finished(&self) -> bool {
+        !self.addr.is_alive()
+    }
 }
 
-impl<T: JoinableHandle, TX> Handle<T, TX> {
-    /// Wrapper on `tx.send(msg).unwrap()`.
-    pub fn send(&self, msg: TX) {
-        self.tx.send(msg).unwrap();
+/// Result of [`Handle::stop_timeout()`].
+///
+/// [`Handle::stop_timeout()`]: struct.Handle.html#method.stop_timeout
+pub enum StopOutcome<H, O = ()> {
+    /// The actor stopped normally. Contains the value returned by `on_stop`.
+    Stopped(O),
+    /// The actor did not stop in time. Contains the handle, so that the owner can wait
+    /// again.
+    TimedOut(H),
+    /// The actor panicked, before or while stopping. Contains the panic payload.
+    Panicked(Box<dyn Any + Send + 'static>),
+}
+
+impl<H, O> fmt::Debug for StopOutcome<H, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StopOutcome::Stopped(_) => f.write_str("Stopped(..)"),
+            StopOutcome::TimedOut(_) => f.write_str("TimedOut(..)"),
+            StopOutcome::Panicked(_) => f.write_str("Panicked(..)"),
+        }
     }
-    #[allow(unused_must_use)]
-    /// Asks the actor to stop and waits (blocking) for it to stop.
-    pub fn stop(self) {
-        self.kill.send(());
-        self.join_handle.join();
+}
+
+impl<T: JoinableHandle, TX> Deref for Handle<T, TX> {
+    type Target = Addr<TX>;
+    fn deref(&self) -> &Addr<TX> {
+        &self.addr
+    }
+}
+
+/// `Handle` with its types erased, so that handles of different actors can be kept
+/// together.
+pub(crate) struct AnyHandle {
+    pub stop: Box<dyn Fn() + Send>,
+    pub join: Box<dyn FnOnce() -> std::thread::Result<()> + Send>,
+}
+
+impl<T, TX> From<Handle<T, TX>> for AnyHandle
+where
+    T: JoinableHandle + Send + 'static,
+    TX: Send + 'static,
+{
+    fn from(handle: Handle<T, TX>) -> Self {
+        let Handle { join_handle, addr } = handle;
+        AnyHandle {
+            stop: Box::new(move || addr.send_stop()),
+            join: Box::new(move || join_handle.join().map(drop)),
+        }
     }
 }
@@ -0,0 +1,225 @@
+//! Running an actor on the current thread, e.g. when it uses GUI objects that must stay
+//! on the main thread.
+//!
+//! ```rust,ignore
+//! use movie::local::LocalJoinHandle;
+//! use movie::{Pump, Pumped};
+//!
+//! // Blocks until the actor stops, another thread gets its handle first
+//! let (handle_tx, handle_rx) = std::sync::mpsc::channel();
+//! std::thread::spawn(move || {
+//!     let window: movie::Handle<LocalJoinHandle<()>, WindowActor::Input> =
+//!         handle_rx.recv().unwrap();
+//!     window.send(WindowActor::Input::SetTitle("movie".to_string())).unwrap();
+//!     window.stop().unwrap();
+//! });
+//! WindowActor::Actor {}.run_with_handle(|handle| handle_tx.send(handle).unwrap());
+//!
+//! // Or run in steps by an existing event loop
+//! let mut window = Pump::new(WindowActor::Actor {});
+//! let addr = window.addr();
+//! event_loop.on_idle(move || match window.pump() {
+//!     Pumped::Busy | Pumped::Idle(_) => true,
+//!     Pumped::Stopped(_) => false,
+//! });
+//! ```
+
+use crate::actor::{run_step, Actor, Event, Step};
+use crate::context::Context;
+use crate::local_data::LocalData;
+use crate::mailbox::{Addr, Mailbox};
+use crate::{Handle, JoinableHandle, Slot};
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Messages handled by one [`Pump::pump()`] call, so that it doesn't hold up the event
+/// loop for long.
+///
+/// [`Pump::pump()`]: struct.Pump.html#method.pump
+pub const PUMP_BUDGET: usize = 16;
+
+/// Runs the actor on the current thread until it stops (with `ctx.stop()`, as there is
+/// no handle to stop it with). Returns the value of `on_stop`.
+pub fn run_here<A: Actor>(actor: A) -> A::Output {
+    let (addr, mailbox) = A::mailbox();
+    actor.run(Context::new(A::name(), addr), mailbox)
+}
+
+/// Like [`run_here()`], but first calls `f` with the actor's handle, which can be sent
+/// to another thread to control the actor from there.
+///
+/// The value of `on_stop`, or the panic payload if the actor panicked, is returned by the
+/// handle's `stop()` or `join()`, which must not be called on this thread. If the actor
+/// panics, this returns normally.
+///
+/// [`run_here()`]: fn.run_here.html
+pub fn run_with_handle<A: Actor>(
+    actor: A,
+    f: impl FnOnce(Handle<LocalJoinHandle<A::Output>, A::Input>),
+) {
+    let (addr, mailbox) = A::mailbox();
+    let result = Arc::new(Slot::new());
+    f(Handle {
+        join_handle: LocalJoinHandle {
+            result: result.clone(),
+        },
+        addr: addr.clone(),
+    });
+    let ctx = Context::new(A::name(), addr);
+    result.set(catch_unwind(AssertUnwindSafe(|| actor.run(ctx, mailbox))));
+}
+
+/// Handle to an actor running on another thread, see [`run_with_handle()`].
+///
+/// [`run_with_handle()`]: fn.run_with_handle.html
+pub struct LocalJoinHandle<T> {
+    result: Arc<Slot<T>>,
+}
+
+impl<T> JoinableHandle for LocalJoinHandle<T> {
+    type Output = T;
+    fn join(self) -> std::thread::Result<T> {
+        self.result.wait()
+    }
+}
+
+/// Actor run in steps by [`pump()`], e.g. from an event loop. Nothing runs between the
+/// calls.
+///
+/// The handlers are called directly, `Actor::run()` is not used, so actors whose handlers
+/// only work in `run()` (see `Actor::RUN_ONLY`) are rejected at compile time. `Pump` keeps
+/// the actor's `local_data` between the calls, so it can't be sent to another thread.
+///
+/// [`pump()`]: #method.pump
+pub struct Pump<A: Actor> {
+    stepper: Stepper<A>,
+    local: LocalData,
+}
+
+/// `Pump` without `local_data`, which can be sent between threads (as `ThreadPool` does).
+pub(crate) struct Stepper<A: Actor> {
+    /// Taken by `on_stop`.
+    actor: Option<A>,
+    ctx: Context<A::Input>,
+    /// Dropped when the actor stops.
+    mailbox: Option<Mailbox<A::Input>>,
+    /// Set once `on_init` ran.
+    initialized: bool,
+}
+
+/// Result of [`Pump::pump()`].
+///
+/// [`Pump::pump()`]: struct.Pump.html#method.pump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pumped<O> {
+    /// There is more to do, `pump()` should be called again soon.
+    Busy,
+    /// Nothing to do until this time (the next tick or timer), or until a message
+    /// arrives, see [`Pump::set_waker()`].
+    ///
+    /// [`Pump::set_waker()`]: struct.Pump.html#method.set_waker
+    Idle(Instant),
+    /// The actor stopped, `on_stop` returned this.
+    Stopped(O),
+}
+
+impl<A: Actor> Pump<A> {
+    /// Prepares the actor. `on_init` runs in the first `pump()`.
+    pub fn new(actor: A) -> Self {
+        let stepper = Stepper::new(actor);
+        let local = LocalData::new(stepper.ctx.addr().id());
+        Pump { stepper, local }
+    }
+
+    /// Address of the actor, can be sent to other threads.
+    pub fn addr(&self) -> Addr<A::Input> {
+        self.stepper.addr()
+    }
+
+    /// Sets a function to call every time a message or a stop request arrives, from the
+    /// sending thread, e.g. to wake up the event loop. Can be set only once.
+    pub fn set_waker(&self, wake: impl Fn() + Send + Sync + 'static) {
+        self.stepper.set_waker(wake)
+    }
+
+    /// Asks the actor to stop. It stops in a following `pump()`, after handling the
+    /// messages sent before.
+    pub fn request_stop(&self) {
+        self.stepper.request_stop()
+    }
+
+    /// Runs `on_init` (the first time), then handles up to [`PUMP_BUDGET`] waiting
+    /// messages, and the tick and timers that are due. Never waits.
+    ///
+    /// # Panics
+    ///
+    /// If the actor has already stopped.
+    ///
+    /// [`PUMP_BUDGET`]: constant.PUMP_BUDGET.html
+    pub fn pump(&mut self) -> Pumped<A::Output> {
+        let stepper = &mut self.stepper;
+        self.local.enter(|| stepper.pump())
+    }
+}
+
+impl<A: Actor> Stepper<A> {
+    pub fn new(actor: A) -> Self {
+        const {
+            assert!(
+                !A::RUN_ONLY,
+                "the actor's handlers only work in `Actor::run()`, it can't be pumped"
+            );
+        }
+        let (addr, mailbox) = A::mailbox();
+        Stepper {
+            actor: Some(actor),
+            ctx: Context::new(A::name(), addr),
+            mailbox: Some(mailbox),
+            initialized: false,
+        }
+    }
+
+    pub fn addr(&self) -> Addr<A::Input> {
+        self.ctx.addr().clone()
+    }
+
+    pub fn set_waker(&self, wake: impl Fn() + Send + Sync + 'static) {
+        self.mailbox
+            .as_ref()
+            .expect("the actor has stopped")
+            .set_waker(wake);
+    }
+
+    pub fn request_stop(&self) {
+        self.ctx.addr().send_stop();
+    }
+
+    pub fn pump(&mut self) -> Pumped<A::Output> {
+        let actor = self.actor.as_mut().expect("the actor has stopped");
+        let ctx = &mut self.ctx;
+        if !self.initialized {
+            self.initialized = true;
+            ctx.set_tick_interval(A::TICK_INTERVAL);
+            ctx.set_tick_schedule(A::TICK_SCHEDULE);
+            actor.on_init(ctx);
+        }
+        let mailbox = self.mailbox.as_ref().unwrap();
+        let step = run_step(ctx, mailbox, PUMP_BUDGET, |ctx, event| match event {
+            Event::Message(message) => actor.on_message(ctx, message),
+            Event::Tick => actor.on_tick(ctx),
+            Event::Timer(timer) => actor.on_timer(ctx, timer),
+        });
+        match step {
+            Step::Busy => Pumped::Busy,
+            Step::Idle(until) => Pumped::Idle(until),
+            Step::Stopping => {
+                let output = self.actor.take().unwrap().on_stop(&mut self.ctx);
+                // `mailbox` is dropped after `on_stop`, like in `Actor::run()`
+                self.mailbox = None;
+                Pumped::Stopped(output)
+            }
+        }
+    }
+}
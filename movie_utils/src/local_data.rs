@@ -0,0 +1,61 @@
+//! State of actors with `local_data`, which never leaves the thread that built it.
+//!
+//! While the actor's handlers run, the state is in a thread-local map, by `Addr::id()`.
+//! `actor!` takes it out for the duration of each handler and puts it back. In
+//! `Actor::run()`, it stays in the map between the handlers, as the thread only runs
+//! this actor. `Pump` and `TestActor` keep it in a [`LocalData`] instead, which is
+//! dropped with them and makes them `!Send`, so they can't be moved to another thread.
+//!
+//! [`LocalData`]: struct.LocalData.html
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static LOCALS: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Takes the state of the actor with this id out of the map. `None` if it's not there,
+/// e.g. because the handler runs on another thread than `on_init` did.
+#[doc(hidden)]
+pub fn take<L: 'static>(id: usize) -> Option<L> {
+    let local = LOCALS.with(|locals| locals.borrow_mut().remove(&id))?;
+    Some(*local.downcast().expect("local data of another type"))
+}
+
+/// Puts the state of the actor with this id in the map.
+#[doc(hidden)]
+pub fn put<L: 'static>(id: usize, local: L) {
+    LOCALS.with(|locals| locals.borrow_mut().insert(id, Box::new(local)));
+}
+
+/// State of an actor between the steps of a runner.
+pub(crate) struct LocalData {
+    id: usize,
+    local: Option<Box<dyn Any>>,
+}
+
+impl LocalData {
+    pub fn new(id: usize) -> Self {
+        LocalData { id, local: None }
+    }
+
+    /// Calls `handlers` with the state in the map, where the handlers look for it. Takes
+    /// it back afterwards, even if they panic.
+    pub fn enter<R>(&mut self, handlers: impl FnOnce() -> R) -> R {
+        struct Exit<'a>(&'a mut LocalData);
+        impl Drop for Exit<'_> {
+            fn drop(&mut self) {
+                let id = self.0.id;
+                self.0.local = LOCALS.with(|locals| locals.borrow_mut().remove(&id));
+            }
+        }
+
+        if let Some(local) = self.local.take() {
+            LOCALS.with(|locals| locals.borrow_mut().insert(self.id, local));
+        }
+        let _exit = Exit(self);
+        handlers()
+    }
+}
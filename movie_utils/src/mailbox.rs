@@ -0,0 +1,423 @@
+//! Actor's channel. [`Addr`] sends messages to it, [`Mailbox`] receives them.
+//!
+//! [`Addr`]: struct.Addr.html
+//! [`Mailbox`]: struct.Mailbox.html
+
+use crate::reply::{AskError, Pending, ReplyTo};
+use crate::scheduler::{self, Scheduled};
+use crate::subscribers::{HasOutput, Subscribers};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Message delivered to actor's channel.
+pub enum Envelope<TX> {
+    /// Message from `Input` enum, to be handled by `on_message`.
+    Message(TX),
+    /// Request to stop the actor, sent by [`Handle::stop()`].
+    ///
+    /// [`Handle::stop()`]: ../struct.Handle.html#method.stop
+    Stop,
+}
+
+/// What happens to a message sent to a full bounded mailbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The sender waits until there is space.
+    Block,
+    /// The sender gets [`SendError::Full`] with the message back.
+    ///
+    /// [`SendError::Full`]: enum.SendError.html#variant.Full
+    Fail,
+    /// The message is dropped.
+    DropNewest,
+    /// The oldest message waiting in the mailbox is dropped to make space.
+    DropOldest,
+}
+
+/// Creates actor's channel with no limit on the number of waiting messages. `Mailbox`
+/// should be moved to the actor, and dropped when it stops running.
+pub fn mailbox<TX>() -> (Addr<TX>, Mailbox<TX>) {
+    new_mailbox(None, Overflow::Block)
+}
+
+/// Creates actor's channel that holds at most `capacity` waiting messages. What happens
+/// when it's full is decided by `overflow`. Stop requests do not count.
+pub fn bounded_mailbox<TX>(capacity: usize, overflow: Overflow) -> (Addr<TX>, Mailbox<TX>) {
+    assert!(capacity > 0, "mailbox capacity must be positive");
+    new_mailbox(Some(capacity), overflow)
+}
+
+fn new_mailbox<TX>(capacity: Option<usize>, overflow: Overflow) -> (Addr<TX>, Mailbox<TX>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            messages: 0,
+            closed: false,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        closed: Condvar::new(),
+        capacity,
+        overflow,
+        subscribers: Subscribers::default(),
+        waker: OnceLock::new(),
+    });
+    let addr = Addr {
+        shared: shared.clone(),
+    };
+    (addr, Mailbox { shared })
+}
+
+struct Shared<TX> {
+    state: Mutex<State<TX>>,
+    /// Signalled when something is put into the queue.
+    not_empty: Condvar,
+    /// Signalled when a message is taken out of the queue or the mailbox is closed.
+    not_full: Condvar,
+    /// Signalled when the mailbox is closed.
+    closed: Condvar,
+    capacity: Option<usize>,
+    overflow: Overflow,
+    subscribers: Subscribers,
+    /// Called after something is put into the queue, see `Mailbox::set_waker()`.
+    waker: OnceLock<Box<dyn Fn() + Send + Sync>>,
+}
+
+struct State<TX> {
+    queue: VecDeque<Envelope<TX>>,
+    /// Number of `Envelope::Message`s in the queue.
+    messages: usize,
+    /// Set when `Mailbox` is dropped.
+    closed: bool,
+}
+
+/// Receiving end of actor's channel.
+pub struct Mailbox<TX> {
+    shared: Arc<Shared<TX>>,
+}
+
+impl<TX> Mailbox<TX> {
+    /// Waits for a message or a stop request, giving up after `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Envelope<TX>, RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(envelope) = state.queue.pop_front() {
+                if let Envelope::Message(_) = envelope {
+                    state.messages -= 1;
+                    self.shared.not_full.notify_one();
+                }
+                return Ok(envelope);
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                    self.shared
+                        .not_empty
+                        .wait_timeout(state, deadline - now)
+                        .unwrap()
+                        .0
+                }
+                None => self.shared.not_empty.wait(state).unwrap(),
+            };
+        }
+    }
+    /// Sets a function to call every time a message or a stop request is put into the
+    /// queue, so that actors without a thread of their own can be woken up. Can be set
+    /// only once.
+    pub(crate) fn set_waker(&self, wake: impl Fn() + Send + Sync + 'static) {
+        if self.shared.waker.set(Box::new(wake)).is_err() {
+            panic!("mailbox waker is already set");
+        }
+    }
+}
+
+impl<TX> Drop for Mailbox<TX> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.closed = true;
+        state.messages = 0;
+        // Drop the messages outside of the lock
+        let queue = std::mem::take(&mut state.queue);
+        drop(state);
+        self.shared.not_full.notify_all();
+        self.shared.closed.notify_all();
+        drop(queue);
+        // Subscribers' receivers disconnect, so they know the actor stopped
+        self.shared.subscribers.clear();
+    }
+}
+
+/// Address of an actor, used to send messages to it.
+///
+/// Unlike [`Handle`], it can be cloned and passed to other threads and actors. It can't
+/// stop the actor, only [`Handle`] can.
+///
+/// [`Handle`]: ../struct.Handle.html
+pub struct Addr<TX> {
+    shared: Arc<Shared<TX>>,
+}
+
+impl<TX> Clone for Addr<TX> {
+    fn clone(&self) -> Self {
+        Addr {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<TX> fmt::Debug for Addr<TX> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Addr")
+            .field("alive", &self.is_alive())
+            .field("queue_len", &self.queue_len())
+            .finish()
+    }
+}
+
+impl<TX> Addr<TX> {
+    /// Sends a message to the actor. Fails, giving the message back, if the actor is no
+    /// longer running.
+    ///
+    /// If the mailbox is bounded and full, what happens depends on its [`Overflow`]
+    /// policy: this may block, fail with [`SendError::Full`], or drop a message.
+    ///
+    /// [`Overflow`]: enum.Overflow.html
+    /// [`SendError::Full`]: enum.SendError.html#variant.Full
+    pub fn send(&self, msg: TX) -> Result<(), SendError<TX>> {
+        self.push(msg, true)
+    }
+    /// Like [`send()`], but never blocks: if the mailbox is full and its policy is
+    /// [`Overflow::Block`], fails with [`SendError::Full`].
+    ///
+    /// [`send()`]: #method.send
+    /// [`Overflow::Block`]: enum.Overflow.html#variant.Block
+    /// [`SendError::Full`]: enum.SendError.html#variant.Full
+    pub fn try_send(&self, msg: TX) -> Result<(), SendError<TX>> {
+        self.push(msg, false)
+    }
+    /// Sends a message with a reply slot and waits (blocking) for the reply.
+    ///
+    /// `msg` builds the message, usually it's a reply-bearing `Input` variant:
+    /// `actor.ask(Input::GetState)` or `actor.ask(|reply| Input::Add(1, 2, reply))`.
+    pub fn ask<R>(&self, msg: impl FnOnce(ReplyTo<R>) -> TX) -> Result<R, AskError> {
+        self.ask_deferred(msg).wait()
+    }
+    /// Like [`ask()`], but gives up after `timeout`.
+    ///
+    /// [`ask()`]: #method.ask
+    pub fn ask_timeout<R>(
+        &self,
+        msg: impl FnOnce(ReplyTo<R>) -> TX,
+        timeout: Duration,
+    ) -> Result<R, AskError> {
+        self.ask_deferred(msg).wait_timeout(timeout)
+    }
+    /// Like [`ask()`], but does not wait for the reply. Returns a [`Pending`] reply
+    /// that can be waited for later.
+    ///
+    /// [`ask()`]: #method.ask
+    /// [`Pending`]: ../reply/struct.Pending.html
+    pub fn ask_deferred<R>(&self, msg: impl FnOnce(ReplyTo<R>) -> TX) -> Pending<R> {
+        let (reply_to, pending) = ReplyTo::channel();
+        // If the message can't be delivered, `reply_to` is dropped with it, and waiting
+        // for the reply will fail.
+        let _ = self.send(msg(reply_to));
+        pending
+    }
+    /// Returns `false` if the actor has stopped or panicked.
+    pub fn is_alive(&self) -> bool {
+        !self.shared.state.lock().unwrap().closed
+    }
+    /// Number of messages waiting in the mailbox.
+    pub fn queue_len(&self) -> usize {
+        self.shared.state.lock().unwrap().messages
+    }
+    /// Maximum number of waiting messages, `None` if the mailbox is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity
+    }
+    /// Identifies the actor: addresses of the same actor have the same id. Once all
+    /// addresses of an actor are dropped, its id can be reused.
+    pub fn id(&self) -> usize {
+        Arc::as_ptr(&self.shared) as *const () as usize
+    }
+    /// Waits until the mailbox is dropped, i.e. the actor stopped running. Returns `false`
+    /// if it's still running after `timeout`.
+    pub(crate) fn wait_closed(&self, timeout: Duration) -> bool {
+        let state = self.shared.state.lock().unwrap();
+        let (state, _) = self
+            .shared
+            .closed
+            .wait_timeout_while(state, timeout, |state| !state.closed)
+            .unwrap();
+        state.closed
+    }
+    /// Asks the actor to stop, without waiting for it. Ignores the capacity.
+    pub(crate) fn send_stop(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        if !state.closed {
+            state.queue.push_back(Envelope::Stop);
+            self.shared.not_empty.notify_one();
+            drop(state);
+            self.wake();
+        }
+    }
+
+    fn push(&self, msg: TX, block: bool) -> Result<(), SendError<TX>> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if state.closed {
+                return Err(SendError::Closed(msg));
+            }
+            match self.shared.capacity {
+                Some(capacity) if state.messages >= capacity => (),
+                _ => break,
+            }
+            match self.shared.overflow {
+                Overflow::Block if block => state = self.shared.not_full.wait(state).unwrap(),
+                Overflow::Block | Overflow::Fail => return Err(SendError::Full(msg)),
+                Overflow::DropNewest => return Ok(()),
+                Overflow::DropOldest => {
+                    // Stop requests are kept
+                    let oldest = state
+                        .queue
+                        .iter()
+                        .position(|envelope| matches!(envelope, Envelope::Message(_)));
+                    if let Some(oldest) = oldest {
+                        state.queue.remove(oldest);
+                        state.messages -= 1;
+                    }
+                    break;
+                }
+            }
+        }
+        state.queue.push_back(Envelope::Message(msg));
+        state.messages += 1;
+        self.shared.not_empty.notify_one();
+        drop(state);
+        self.wake();
+        Ok(())
+    }
+
+    fn wake(&self) {
+        if let Some(wake) = self.shared.waker.get() {
+            wake();
+        }
+    }
+}
+
+impl<TX> Addr<TX>
+where
+    TX: HasOutput,
+    TX::Output: Clone + Send + 'static,
+{
+    /// Subscribes to the events the actor emits with `ctx.emit()`. Every subscriber gets
+    /// its own copy of every event emitted after it subscribed.
+    ///
+    /// The receiver disconnects when the actor stops. Dropping it unsubscribes.
+    pub fn subscribe(&self) -> Receiver<TX::Output> {
+        self.shared
+            .subscribers
+            .subscribe(|| self.shared.state.lock().unwrap().closed)
+    }
+    /// Used by `Context::emit()`.
+    pub(crate) fn emit(&self, event: TX::Output) {
+        self.shared.subscribers.emit(event);
+    }
+}
+
+impl<TX: Send + 'static> Addr<TX> {
+    /// Sends a message to the actor after `delay`, without blocking. Returns a
+    /// [`Scheduled`] that can cancel it.
+    ///
+    /// All delayed messages are sent from one shared thread. It never blocks: if the
+    /// mailbox is full and its policy is [`Overflow::Block`], the message is dropped.
+    ///
+    /// [`Scheduled`]: ../scheduler/struct.Scheduled.html
+    /// [`Overflow::Block`]: enum.Overflow.html#variant.Block
+    pub fn send_after(&self, delay: Duration, msg: TX) -> Scheduled {
+        self.send_at(Instant::now() + delay, msg)
+    }
+    /// Like [`send_after()`], but sends the message at `at`.
+    ///
+    /// [`send_after()`]: #method.send_after
+    pub fn send_at(&self, at: Instant, msg: TX) -> Scheduled {
+        let addr = self.clone();
+        let mut msg = Some(msg);
+        scheduler::schedule(
+            at,
+            None,
+            Box::new(move || match msg.take() {
+                Some(msg) => addr.try_send(msg).is_ok(),
+                None => false,
+            }),
+        )
+    }
+    /// Sends a message made by `msg` every `period`, starting `period` from now, until
+    /// cancelled or the actor stops. Like [`send_after()`], never blocks. When the shared
+    /// thread is late by more than a period, the missed messages are skipped.
+    ///
+    /// [`send_after()`]: #method.send_after
+    pub fn send_interval(
+        &self,
+        period: Duration,
+        mut msg: impl FnMut() -> TX + Send + 'static,
+    ) -> Scheduled {
+        let addr = self.clone();
+        scheduler::schedule(
+            Instant::now() + period,
+            Some(period),
+            Box::new(move || !matches!(addr.try_send(msg()), Err(SendError::Closed(_)))),
+        )
+    }
+}
+
+/// Error returned by [`Addr::send()`]. Contains the message that could not be delivered.
+///
+/// [`Addr::send()`]: struct.Addr.html#method.send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendError<T> {
+    /// The actor is no longer running (it stopped or panicked).
+    Closed(T),
+    /// The mailbox is full, see [`Overflow`].
+    ///
+    /// [`Overflow`]: enum.Overflow.html
+    Full(T),
+}
+
+impl<T> SendError<T> {
+    /// Returns the message that could not be delivered.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Closed(msg) | SendError::Full(msg) => msg,
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendError::Closed(_) => f.write_str("Closed(..)"),
+            SendError::Full(_) => f.write_str("Full(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendError::Closed(_) => f.write_str("sending to a stopped actor"),
+            SendError::Full(_) => f.write_str("actor's mailbox is full"),
+        }
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
@@ -0,0 +1,235 @@
+//! Starting actors connected output-to-input.
+//!
+//! ```rust,ignore
+//! use movie::Pipeline;
+//! use std::time::Duration;
+//!
+//! let mut pipeline = Pipeline::new();
+//! let capture = pipeline.add("capture", CaptureActor::Actor {});
+//! let parse = pipeline.add("parse", ParseActor::Actor { lines: 0 });
+//! let publish = pipeline.add("publish", PublishActor::Actor {});
+//! // Every `CaptureActor::Output` event is mapped to a message for `parse`
+//! pipeline.connect(&capture, &parse, |frame: CaptureActor::Output| match frame {
+//!     CaptureActor::Output::Frame(data) => Some(ParseActor::Input::Parse(data)),
+//!     CaptureActor::Output::Idle => None,
+//! });
+//! pipeline.connect(&parse, &publish, |line: ParseActor::Output| {
+//!     Some(PublishActor::Input::Publish(line))
+//! });
+//! // Starts `publish`, `parse` and `capture`, in that order
+//! let system = pipeline.start().unwrap();
+//! capture.send(CaptureActor::Input::Start).unwrap();
+//! // Stops `capture`, `parse` and `publish`, in that order
+//! system.shutdown(Duration::from_secs(5));
+//! ```
+
+use crate::actor::{thread_builder, Actor};
+use crate::context::Context;
+use crate::mailbox::{Addr, SendError};
+use crate::subscribers::HasOutput;
+use crate::system::ActorSystem;
+use crate::{AnyHandle, Handle};
+
+use std::fmt;
+use std::io;
+use std::sync::mpsc::Receiver;
+use std::thread::{self, JoinHandle};
+
+/// Actors that are not running yet, and connections between them.
+///
+/// Actors get their addresses (and mailboxes) when added, so messages can be sent to
+/// them and connections made before they start. No event is lost, even the ones emitted
+/// in `on_init`.
+#[derive(Default)]
+pub struct Pipeline {
+    actors: Vec<Node>,
+}
+
+struct Node {
+    name: String,
+    /// See `Addr::id()`.
+    id: usize,
+    start: Box<dyn FnOnce() -> io::Result<AnyHandle> + Send>,
+    /// Forward the actor's events, each to a connected actor.
+    forwarders: Vec<Box<dyn FnOnce() -> JoinHandle<()> + Send>>,
+    /// Indices of actors this one sends events to.
+    targets: Vec<usize>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Adds an actor, to be started in its own thread by [`start()`]. Returns its
+    /// address. `name` is used in `ShutdownReport`.
+    ///
+    /// [`start()`]: #method.start
+    pub fn add<A>(&mut self, name: impl Into<String>, actor: A) -> Addr<A::Input>
+    where
+        A: Actor + Send + 'static,
+        A::Input: Send + 'static,
+        A::Output: Send + 'static,
+    {
+        let (addr, mailbox) = A::mailbox();
+        let handle_addr = addr.clone();
+        let builder = thread_builder(&actor);
+        let start = move || {
+            let ctx = Context::new(A::name(), handle_addr.clone());
+            let addr = handle_addr;
+            let join_handle = builder.spawn(move || actor.run(ctx, mailbox))?;
+            Ok(Handle { join_handle, addr }.into())
+        };
+        self.actors.push(Node {
+            name: name.into(),
+            id: addr.id(),
+            start: Box::new(start),
+            forwarders: Vec::new(),
+            targets: Vec::new(),
+        });
+        addr
+    }
+
+    /// Sends every event emitted by `from` to `to`, after mapping it with `map`. Events
+    /// mapped to `None` are skipped.
+    ///
+    /// `to` is started before `from` and stopped after it, and gets all the events `from`
+    /// emitted before stopping.
+    ///
+    /// # Panics
+    ///
+    /// If `from` or `to` was not added to this pipeline.
+    pub fn connect<RX, TX, F>(&mut self, from: &Addr<RX>, to: &Addr<TX>, map: F)
+    where
+        RX: HasOutput,
+        RX::Output: Clone + Send + 'static,
+        TX: Send + 'static,
+        F: FnMut(RX::Output) -> Option<TX> + Send + 'static,
+    {
+        let source = self.index_of(from);
+        let target = self.index_of(to);
+        // Subscribed now, so that the forwarder gets all events
+        let events = from.subscribe();
+        let to = to.clone();
+        let node = &mut self.actors[source];
+        node.forwarders.push(Box::new(move || {
+            thread::spawn(move || forward(events, to, map))
+        }));
+        node.targets.push(target);
+    }
+
+    /// Starts the actors, each after the actors it's connected to, and returns an
+    /// [`ActorSystem`] that stops them in reverse order.
+    ///
+    /// Fails without starting any actor if the connections form a cycle. If an actor's
+    /// thread can't be spawned, the actors started before it are asked to stop, and the
+    /// others are not started.
+    ///
+    /// [`ActorSystem`]: ../system/struct.ActorSystem.html
+    pub fn start(self) -> Result<ActorSystem, PipelineError> {
+        // Repeatedly pick the first actor whose targets have all been picked
+        let mut order = Vec::with_capacity(self.actors.len());
+        let mut picked = vec![false; self.actors.len()];
+        while order.len() < self.actors.len() {
+            let next = (0..self.actors.len()).find(|&i| {
+                !picked[i] && self.actors[i].targets.iter().all(|&target| picked[target])
+            });
+            match next {
+                Some(next) => {
+                    picked[next] = true;
+                    order.push(next);
+                }
+                None => {
+                    let names = (0..self.actors.len())
+                        .filter(|&i| !picked[i])
+                        .map(|i| self.actors[i].name.clone())
+                        .collect();
+                    return Err(PipelineError::Cycle(names));
+                }
+            }
+        }
+
+        let mut actors: Vec<Option<Node>> = self.actors.into_iter().map(Some).collect();
+        let mut system = ActorSystem::new();
+        for i in order {
+            let node = actors[i].take().unwrap();
+            let AnyHandle { stop, join } = match (node.start)() {
+                Ok(handle) => handle,
+                Err(error) => {
+                    system.request_stop();
+                    return Err(PipelineError::Spawn(node.name, error));
+                }
+            };
+            // Started after the actor, as they only exit once it stops. Events emitted
+            // in the meantime wait in the channels.
+            let forwarders: Vec<JoinHandle<()>> =
+                node.forwarders.into_iter().map(|spawn| spawn()).collect();
+            // The actor counts as stopped once its last events are forwarded
+            let join = Box::new(move || {
+                let mut result = join();
+                for forwarder in forwarders {
+                    let forwarded = forwarder.join();
+                    if result.is_ok() {
+                        result = forwarded;
+                    }
+                }
+                result
+            });
+            system.push(node.name, AnyHandle { stop, join });
+        }
+        Ok(system)
+    }
+
+    fn index_of<TX>(&self, addr: &Addr<TX>) -> usize {
+        let id = addr.id();
+        self.actors
+            .iter()
+            .position(|node| node.id == id)
+            .expect("actor is not a part of the pipeline")
+    }
+}
+
+/// Error returned by [`Pipeline::start()`].
+///
+/// [`Pipeline::start()`]: struct.Pipeline.html#method.start
+#[derive(Debug)]
+pub enum PipelineError {
+    /// The actors are connected in a cycle, so there is no order to start them in.
+    /// Contains names of the actors in the cycle and of the actors connected to them.
+    Cycle(Vec<String>),
+    /// Thread of the named actor could not be spawned.
+    Spawn(String, io::Error),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineError::Cycle(names) => {
+                write!(f, "actors connected in a cycle: {}", names.join(", "))
+            }
+            PipelineError::Spawn(name, error) => {
+                write!(f, "failed to spawn thread of {}: {}", name, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Cycle(_) => None,
+            PipelineError::Spawn(_, error) => Some(error),
+        }
+    }
+}
+
+/// Runs until `events` disconnect (the source stopped), or the target stops.
+fn forward<TX, O>(events: Receiver<O>, to: Addr<TX>, mut map: impl FnMut(O) -> Option<TX>) {
+    for event in events {
+        if let Some(message) = map(event) {
+            if let Err(SendError::Closed(_)) = to.send(message) {
+                break;
+            }
+        }
+    }
+}
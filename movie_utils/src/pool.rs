@@ -0,0 +1,330 @@
+//! Running many actors on a few threads.
+//!
+//! ```rust,ignore
+//! use movie::ThreadPool;
+//!
+//! // Hundreds of mostly idle actors, 4 threads
+//! let pool = ThreadPool::new(4);
+//! let sensors: Vec<_> = (0..500)
+//!     .map(|id| pool.start(SensorActor::Actor { id }))
+//!     .collect();
+//! sensors[0].send(SensorActor::Input::Calibrate).unwrap();
+//! for sensor in sensors {
+//!     sensor.stop().unwrap();
+//! }
+//! ```
+
+use crate::actor::{Actor, Step};
+use crate::local::{Pumped, Stepper};
+use crate::{Handle, JoinableHandle, Slot};
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, VecDeque};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time::Instant;
+
+/// A fixed number of threads running actors, each actor on whichever thread is free.
+///
+/// Actors with something to do (messages, a tick or a timer that's due) take turns, in
+/// the order they got it, handling at most `PUMP_BUDGET` (16) messages per turn. Idle
+/// actors don't use any thread. Handlers should not block for long, as they hold up the
+/// other actors.
+///
+/// The pool calls the actor's handlers directly, `Actor::run()` is not used, each time on
+/// any of its threads. Actors that can't be run this way (see `Actor::RUN_ONLY` and
+/// `Actor::THREAD_BOUND`, e.g. actors with `local_data`) are rejected at compile time.
+/// Dropping the pool doesn't stop the actors, the threads exit once all actors have
+/// stopped.
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+    threads: usize,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    /// Signalled when an actor is ready, a sleeping actor is added, or the pool is
+    /// dropped.
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct Queue {
+    /// Actors with something to do, in the order they got it.
+    ready: VecDeque<Arc<Task>>,
+    /// Idle actors, woken up when their next tick or timer is due.
+    sleeping: BinaryHeap<Reverse<Sleeping>>,
+    /// Actors that haven't stopped yet.
+    actors: usize,
+    /// Set when `ThreadPool` is dropped.
+    dropped: bool,
+}
+
+struct Sleeping {
+    until: Instant,
+    task: Arc<Task>,
+}
+
+impl PartialEq for Sleeping {
+    fn eq(&self, other: &Self) -> bool {
+        self.until == other.until
+    }
+}
+
+impl Eq for Sleeping {}
+
+impl PartialOrd for Sleeping {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Sleeping {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.until.cmp(&other.until)
+    }
+}
+
+/// An actor in the pool. Owned by the queue it's in, or by the thread running it.
+struct Task {
+    state: Mutex<State>,
+    /// When its entry in `sleeping` is due. Older entries are skipped. Only used with
+    /// the queue locked.
+    sleeping_until: Mutex<Option<Instant>>,
+    actor: Mutex<Box<dyn Runnable + Send>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for a message, maybe also in `sleeping`.
+    Idle,
+    /// In `ready`.
+    Ready,
+    Running,
+    /// Running, and got a message in the meantime.
+    RunningWoken,
+    Stopped,
+}
+
+/// Actor running in steps, type-erased.
+trait Runnable {
+    /// Returns `Step::Stopping` once the actor stopped (or panicked), and must not be
+    /// called again.
+    fn step(&mut self) -> Step;
+}
+
+impl ThreadPool {
+    /// Spawns `threads` threads.
+    ///
+    /// # Panics
+    ///
+    /// If `threads` is 0.
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "thread pool needs at least one thread");
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            changed: Condvar::new(),
+        });
+        for _ in 0..threads {
+            let shared = shared.clone();
+            thread::spawn(move || work(&shared));
+        }
+        ThreadPool { shared, threads }
+    }
+
+    /// Number of threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Starts the actor in the pool. It's the pool's counterpart of `movie::start()`.
+    pub fn start<A>(&self, actor: A) -> Handle<PoolJoinHandle<A::Output>, A::Input>
+    where
+        A: Actor + Send + 'static,
+        A::Input: Send + 'static,
+        A::Output: Send + 'static,
+    {
+        const {
+            assert!(
+                !A::THREAD_BOUND,
+                "the actor's handlers must run on one thread, it can't be run in `ThreadPool`"
+            );
+        }
+        let pump = Stepper::new(actor);
+        let addr = pump.addr();
+        let result = Arc::new(Slot::new());
+        let task = Arc::new_cyclic(|task: &Weak<Task>| {
+            // Weak, as the mailbox is owned by the task
+            let task = task.clone();
+            let shared = self.shared.clone();
+            pump.set_waker(move || {
+                if let Some(task) = task.upgrade() {
+                    wake(&shared, &task);
+                }
+            });
+            Task {
+                state: Mutex::new(State::Idle),
+                sleeping_until: Mutex::new(None),
+                actor: Mutex::new(Box::new(PoolActor {
+                    pump: Some(pump),
+                    result: result.clone(),
+                })),
+            }
+        });
+        self.shared.queue.lock().unwrap().actors += 1;
+        // Runs `on_init`
+        wake(&self.shared, &task);
+        Handle {
+            join_handle: PoolJoinHandle { result },
+            addr,
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().dropped = true;
+        self.shared.changed.notify_all();
+    }
+}
+
+/// Handle to an actor started with [`ThreadPool::start()`].
+///
+/// [`ThreadPool::start()`]: struct.ThreadPool.html#method.start
+pub struct PoolJoinHandle<T> {
+    result: Arc<Slot<T>>,
+}
+
+impl<T> JoinableHandle for PoolJoinHandle<T> {
+    type Output = T;
+    fn join(self) -> thread::Result<T> {
+        self.result.wait()
+    }
+}
+
+/// Puts the task in `ready`, unless it's already there or running.
+fn wake(shared: &Shared, task: &Arc<Task>) {
+    // The task's lock is never held while taking the queue's lock
+    {
+        let mut state = task.state.lock().unwrap();
+        match *state {
+            State::Idle => *state = State::Ready,
+            State::Running => {
+                *state = State::RunningWoken;
+                return;
+            }
+            State::Ready | State::RunningWoken | State::Stopped => return,
+        }
+    }
+    shared.queue.lock().unwrap().ready.push_back(task.clone());
+    shared.changed.notify_one();
+}
+
+/// Worker thread.
+fn work(shared: &Shared) {
+    let mut queue = shared.queue.lock().unwrap();
+    loop {
+        // Wake up actors whose tick or timer is due
+        let now = Instant::now();
+        let mut due = Vec::new();
+        while queue
+            .sleeping
+            .peek()
+            .is_some_and(|Reverse(sleeping)| sleeping.until <= now)
+        {
+            let Reverse(sleeping) = queue.sleeping.pop().unwrap();
+            let mut until = sleeping.task.sleeping_until.lock().unwrap();
+            if *until == Some(sleeping.until) {
+                *until = None;
+                drop(until);
+                due.push(sleeping.task);
+            }
+        }
+        if !due.is_empty() {
+            drop(queue);
+            for task in &due {
+                wake(shared, task);
+            }
+            queue = shared.queue.lock().unwrap();
+            continue;
+        }
+
+        if let Some(task) = queue.ready.pop_front() {
+            drop(queue);
+            run(shared, task);
+            queue = shared.queue.lock().unwrap();
+            continue;
+        }
+        if queue.dropped && queue.actors == 0 {
+            // Other threads may be waiting for the next sleeping actor
+            shared.changed.notify_all();
+            return;
+        }
+        queue = match queue.sleeping.peek() {
+            Some(Reverse(sleeping)) => {
+                let timeout = sleeping.until.saturating_duration_since(now);
+                shared.changed.wait_timeout(queue, timeout).unwrap().0
+            }
+            None => shared.changed.wait(queue).unwrap(),
+        };
+    }
+}
+
+/// Gives the task one turn.
+fn run(shared: &Shared, task: Arc<Task>) {
+    *task.state.lock().unwrap() = State::Running;
+    let step = task.actor.lock().unwrap().step();
+    let mut state = task.state.lock().unwrap();
+    match step {
+        Step::Busy => *state = State::Ready,
+        Step::Idle(_) if *state == State::RunningWoken => *state = State::Ready,
+        Step::Idle(until) => {
+            *state = State::Idle;
+            drop(state);
+            let mut queue = shared.queue.lock().unwrap();
+            let mut sleeping_until = task.sleeping_until.lock().unwrap();
+            if *sleeping_until != Some(until) {
+                *sleeping_until = Some(until);
+                drop(sleeping_until);
+                queue.sleeping.push(Reverse(Sleeping { until, task }));
+                // It may need to wake up before the other threads would
+                shared.changed.notify_one();
+            }
+            return;
+        }
+        Step::Stopping => {
+            *state = State::Stopped;
+            drop(state);
+            shared.queue.lock().unwrap().actors -= 1;
+            shared.changed.notify_all();
+            return;
+        }
+    }
+    drop(state);
+    // To the back of the queue, after the actors that were waiting
+    shared.queue.lock().unwrap().ready.push_back(task);
+    shared.changed.notify_one();
+}
+
+struct PoolActor<A: Actor> {
+    /// Dropped when the actor stops or panics.
+    pump: Option<Stepper<A>>,
+    result: Arc<Slot<A::Output>>,
+}
+
+impl<A: Actor> Runnable for PoolActor<A> {
+    fn step(&mut self) -> Step {
+        let pump = self.pump.as_mut().unwrap();
+        let result = match catch_unwind(AssertUnwindSafe(|| pump.pump())) {
+            Ok(Pumped::Busy) => return Step::Busy,
+            Ok(Pumped::Idle(until)) => return Step::Idle(until),
+            Ok(Pumped::Stopped(output)) => Ok(output),
+            Err(payload) => Err(payload),
+        };
+        // The mailbox is closed before the result is available, like with a thread
+        self.pump = None;
+        self.result.set(result);
+        Step::Stopping
+    }
+}
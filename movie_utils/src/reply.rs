@@ -0,0 +1,93 @@
+//! Replies to messages, see [`Addr::ask()`].
+//!
+//! [`Addr::ask()`]: ../mailbox/struct.Addr.html#method.ask
+
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::time::Duration;
+
+/// Where to send the reply to a message.
+///
+/// Reply-bearing `Input` variants (e.g. `GetState -> u64`) get it as their last field.
+/// The actor answers with [`send()`]. Dropping it without answering makes the asking side
+/// get [`AskError::Closed`].
+///
+/// [`send()`]: #method.send
+/// [`AskError::Closed`]: enum.AskError.html#variant.Closed
+pub struct ReplyTo<R> {
+    tx: Sender<R>,
+}
+
+impl<R> ReplyTo<R> {
+    /// Creates a reply slot and the receiving end of it.
+    pub fn channel() -> (Self, Pending<R>) {
+        let (tx, rx) = channel();
+        (ReplyTo { tx }, Pending { rx })
+    }
+
+    /// Sends the reply. If the asking side is no longer waiting for it (e.g. it timed
+    /// out), the reply is dropped.
+    pub fn send(self, reply: R) {
+        let _ = self.tx.send(reply);
+    }
+}
+
+impl<R> fmt::Debug for ReplyTo<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ReplyTo")
+    }
+}
+
+/// Reply that may not have arrived yet. Returned by [`Addr::ask_deferred()`].
+///
+/// [`Addr::ask_deferred()`]: ../mailbox/struct.Addr.html#method.ask_deferred
+pub struct Pending<R> {
+    rx: Receiver<R>,
+}
+
+impl<R> Pending<R> {
+    /// Waits (blocking) for the reply.
+    pub fn wait(self) -> Result<R, AskError> {
+        self.rx.recv().map_err(|_| AskError::Closed)
+    }
+
+    /// Waits (blocking) for the reply, giving up after `timeout`.
+    pub fn wait_timeout(self, timeout: Duration) -> Result<R, AskError> {
+        self.rx.recv_timeout(timeout).map_err(|err| match err {
+            RecvTimeoutError::Timeout => AskError::Timeout,
+            RecvTimeoutError::Disconnected => AskError::Closed,
+        })
+    }
+
+    /// Returns the reply if it has already arrived, without blocking.
+    pub fn try_wait(&self) -> Result<Option<R>, AskError> {
+        match self.rx.try_recv() {
+            Ok(reply) => Ok(Some(reply)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(AskError::Closed),
+        }
+    }
+}
+
+/// Reason why no reply was received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AskError {
+    /// The actor dropped the [`ReplyTo`] without answering, e.g. because it stopped or
+    /// panicked, or the message was dropped because the mailbox was full.
+    ///
+    /// [`ReplyTo`]: struct.ReplyTo.html
+    Closed,
+    /// The reply did not arrive in time.
+    Timeout,
+}
+
+impl fmt::Display for AskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AskError::Closed => f.write_str("actor did not reply"),
+            AskError::Timeout => f.write_str("timed out waiting for reply"),
+        }
+    }
+}
+
+impl std::error::Error for AskError {}
@@ -0,0 +1,199 @@
+//! Delayed and repeated messages, see `Addr::send_after()` and `Addr::send_interval()`.
+//!
+//! All of them share one thread, running a hashed timer wheel: a ring of slots, one per
+//! millisecond. A message due in `n` milliseconds goes to the slot `n` ahead of the
+//! current one, and is sent when the wheel gets there, after `n / SLOTS` full turns.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SLOTS: u64 = 256;
+
+/// A delayed or repeated message, returned by [`Addr::send_after()`] and
+/// [`Addr::send_interval()`]. Dropping it does not cancel the message.
+///
+/// [`Addr::send_after()`]: ../mailbox/struct.Addr.html#method.send_after
+/// [`Addr::send_interval()`]: ../mailbox/struct.Addr.html#method.send_interval
+#[derive(Debug, Clone)]
+pub struct Scheduled {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Scheduled {
+    /// Cancels the message, or all the following messages if it's repeated. Messages
+    /// already sent stay in the mailbox.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+    /// Returns `true` if [`cancel()`] was called.
+    ///
+    /// [`cancel()`]: #method.cancel
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+struct Task {
+    /// When it's due.
+    at: Instant,
+    /// Full turns of the wheel left before it's due.
+    rounds: u64,
+    /// Time until sending again, if repeated.
+    period: Option<Duration>,
+    cancelled: Arc<AtomicBool>,
+    /// Sends the message. Returns `false` if the actor is gone.
+    send: Box<dyn FnMut() -> bool + Send>,
+}
+
+struct Wheel {
+    slots: Vec<Vec<Task>>,
+    /// Time of tick 0.
+    start: Instant,
+    /// Last tick whose slot was emptied.
+    tick: u64,
+    len: usize,
+}
+
+struct Shared {
+    wheel: Mutex<Wheel>,
+    /// Notified when a task is added, so that the thread doesn't oversleep.
+    added: Condvar,
+}
+
+/// Schedules `send` at `at`, and every `period` after that if it's set.
+pub(crate) fn schedule(
+    at: Instant,
+    period: Option<Duration>,
+    send: Box<dyn FnMut() -> bool + Send>,
+) -> Scheduled {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let shared = shared();
+    shared.wheel.lock().unwrap().insert(Task {
+        at,
+        rounds: 0,
+        period,
+        cancelled: cancelled.clone(),
+        send,
+    });
+    shared.added.notify_one();
+    Scheduled { cancelled }
+}
+
+fn shared() -> &'static Shared {
+    static SHARED: OnceLock<Shared> = OnceLock::new();
+    let mut spawn = false;
+    let shared = SHARED.get_or_init(|| {
+        spawn = true;
+        Shared {
+            wheel: Mutex::new(Wheel {
+                slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+                start: Instant::now(),
+                tick: 0,
+                len: 0,
+            }),
+            added: Condvar::new(),
+        }
+    });
+    if spawn {
+        thread::Builder::new()
+            .name("movie-scheduler".to_string())
+            .spawn(move || run(shared))
+            .expect("failed to spawn the scheduler thread");
+    }
+    shared
+}
+
+fn run(shared: &Shared) {
+    let mut wheel = shared.wheel.lock().unwrap();
+    loop {
+        let due = wheel.advance(Instant::now());
+        if !due.is_empty() {
+            // Messages are sent without the lock, so sending can schedule more
+            drop(wheel);
+            let repeated: Vec<Task> = due.into_iter().filter_map(send).collect();
+            wheel = shared.wheel.lock().unwrap();
+            for task in repeated {
+                wheel.insert(task);
+            }
+            continue;
+        }
+        wheel = match wheel.next_wakeup() {
+            Some(at) => {
+                let timeout = at.saturating_duration_since(Instant::now());
+                shared.added.wait_timeout(wheel, timeout).unwrap().0
+            }
+            None => shared.added.wait(wheel).unwrap(),
+        };
+    }
+}
+
+/// Sends the task's message, returning the task if it should be sent again.
+fn send(mut task: Task) -> Option<Task> {
+    if task.cancelled.load(Ordering::SeqCst) {
+        return None;
+    }
+    // A panicking message factory cancels its task, other tasks are unaffected
+    let alive = catch_unwind(AssertUnwindSafe(|| (task.send)())).unwrap_or(false);
+    let period = match task.period {
+        Some(period) if alive => period,
+        _ => return None,
+    };
+    let now = Instant::now();
+    task.at += period;
+    if task.at < now {
+        // Too late, the missed messages are skipped
+        task.at = now + period;
+    }
+    Some(task)
+}
+
+impl Wheel {
+    fn insert(&mut self, mut task: Task) {
+        if self.len == 0 {
+            // The wheel doesn't turn when it's empty
+            self.tick = self.ticks_at(Instant::now());
+        }
+        // Rounded up, so that it's never sent early
+        let due = self.ticks_at(task.at + Duration::from_nanos(999_999));
+        let due = due.max(self.tick + 1);
+        task.rounds = (due - self.tick - 1) / SLOTS;
+        self.slots[(due % SLOTS) as usize].push(task);
+        self.len += 1;
+    }
+
+    /// Turns the wheel up to `now`, returning tasks that are due.
+    fn advance(&mut self, now: Instant) -> Vec<Task> {
+        let mut due = Vec::new();
+        let now = self.ticks_at(now);
+        while self.tick < now && self.len > 0 {
+            self.tick += 1;
+            let slot = &mut self.slots[(self.tick % SLOTS) as usize];
+            let mut i = 0;
+            while i < slot.len() {
+                if slot[i].rounds == 0 {
+                    due.push(slot.swap_remove(i));
+                    self.len -= 1;
+                } else {
+                    slot[i].rounds -= 1;
+                    i += 1;
+                }
+            }
+        }
+        due
+    }
+
+    /// Time of the next slot with tasks, `None` if the wheel is empty.
+    fn next_wakeup(&self) -> Option<Instant> {
+        (1..=SLOTS)
+            .map(|ahead| self.tick + ahead)
+            .find(|tick| !self.slots[(tick % SLOTS) as usize].is_empty())
+            .map(|tick| self.start + Duration::from_millis(tick))
+    }
+
+    fn ticks_at(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.start).as_millis() as u64
+    }
+}
@@ -0,0 +1,63 @@
+//! Subscribers of actor's events, see [`Context::emit()`] and [`Addr::subscribe()`].
+//!
+//! The type of the events is given by the actor's input, which implements [`HasOutput`],
+//! so subscribers can't ask for events of a type the actor never emits.
+//!
+//! [`Context::emit()`]: ../context/struct.Context.html#method.emit
+//! [`Addr::subscribe()`]: ../mailbox/struct.Addr.html#method.subscribe
+//! [`HasOutput`]: trait.HasOutput.html
+
+use std::any::Any;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+/// Input of an actor that emits events, tying the type of the events to it. `actor!`
+/// implements it for `Input` when `output` is set.
+///
+/// `Addr<Input>::subscribe()` returns a `Receiver<Output>`, and `Context<Input>::emit()`
+/// only takes `Output`.
+pub trait HasOutput {
+    type Output;
+}
+
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    /// `Vec<Sender<O>>`, created by the first subscriber. The mailbox's input decides `O`
+    /// (see `HasOutput`), so it's the same type for every call.
+    senders: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl Subscribers {
+    /// Adds a subscriber, unless `closed` says the actor has stopped. Then the receiver
+    /// is disconnected from the start.
+    pub fn subscribe<O: Send + 'static>(&self, closed: impl FnOnce() -> bool) -> Receiver<O> {
+        let (sender, receiver) = channel();
+        let mut senders = self.senders.lock().unwrap();
+        // Checked under the lock, so that `clear()` can't run in between
+        if !closed() {
+            senders
+                .get_or_insert_with(|| Box::new(Vec::<Sender<O>>::new()))
+                .downcast_mut::<Vec<Sender<O>>>()
+                .expect("events of another type")
+                .push(sender);
+        }
+        receiver
+    }
+
+    /// Sends `event` to every subscriber, dropping those that hung up.
+    pub fn emit<O: Clone + Send + 'static>(&self, event: O) {
+        let mut senders = self.senders.lock().unwrap();
+        if let Some(senders) = senders.as_mut() {
+            let senders = senders
+                .downcast_mut::<Vec<Sender<O>>>()
+                .expect("events of another type");
+            senders.retain(|sender| sender.send(event.clone()).is_ok());
+        }
+    }
+
+    /// Drops all subscribers, disconnecting their receivers.
+    pub fn clear(&self) {
+        let senders = self.senders.lock().unwrap().take();
+        drop(senders);
+    }
+}
@@ -0,0 +1,366 @@
+//! Restarting actors that panicked.
+//!
+//! ```rust,ignore
+//! use movie::supervisor::{Policy, Strategy, Supervisor};
+//!
+//! let supervisor = Supervisor::start(Policy {
+//!     strategy: Strategy::OneForOne,
+//!     ..Policy::default()
+//! });
+//! // The factory is called again on every restart, so the actor gets fresh data
+//! let parser = supervisor.supervise(|| StreamParsingActor::Actor {
+//!     device: DEFAULT_DEVICE.to_string(),
+//! }.start());
+//! parser.send(Input::ChangeSource("video1".to_string())).unwrap();
+//! supervisor.stop().unwrap();
+//! ```
+
+use crate::mailbox::{Addr, SendError};
+use crate::{AnyHandle, Handle, JoinableHandle};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Which actors are restarted when one of them panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Only the actor that panicked.
+    OneForOne,
+    /// All actors of the supervisor. The others are stopped first.
+    OneForAll,
+}
+
+/// How long to wait before restarting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// Restart immediately.
+    None,
+    /// Always wait the same time.
+    Fixed(Duration),
+    /// Wait `initial`, doubling the time with every restart within the `window`, up to
+    /// `max`.
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// Delay before `n`-th restart (counting from 1).
+    fn delay(&self, n: usize) -> Duration {
+        match *self {
+            Backoff::None => Duration::from_millis(0),
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 1u32
+                    .checked_shl(n.saturating_sub(1) as u32)
+                    .unwrap_or(u32::MAX);
+                initial
+                    .checked_mul(factor)
+                    .map_or(max, |delay| delay.min(max))
+            }
+        }
+    }
+}
+
+/// Restart policy of a [`Supervisor`].
+///
+/// [`Supervisor`]: struct.Supervisor.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    pub strategy: Strategy,
+    /// If there are more restarts than this within `window`, the supervisor stops all
+    /// actors and gives up.
+    pub max_restarts: usize,
+    pub window: Duration,
+    pub backoff: Backoff,
+}
+
+impl Default for Policy {
+    /// One-for-one, at most 3 restarts in 5 seconds, no backoff.
+    fn default() -> Self {
+        Policy {
+            strategy: Strategy::OneForOne,
+            max_restarts: 3,
+            window: Duration::from_secs(5),
+            backoff: Backoff::None,
+        }
+    }
+}
+
+/// Error returned by [`Supervisor::stop()`].
+///
+/// [`Supervisor::stop()`]: struct.Supervisor.html#method.stop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisorError {
+    /// Actors panicked more often than the policy allows, so the supervisor stopped them.
+    TooManyRestarts,
+    /// These actors (numbered from 0, in the order they were supervised) panicked while
+    /// stopping. The other actors were still stopped.
+    PanickedWhileStopping(Vec<usize>),
+}
+
+impl fmt::Display for SupervisorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SupervisorError::TooManyRestarts => f.write_str("actors restarted too many times"),
+            SupervisorError::PanickedWhileStopping(actors) => {
+                write!(f, "{} actors panicked while stopping", actors.len())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SupervisorError {}
+
+/// Watches actors and restarts them if they panic.
+///
+/// Actors that stop without panicking (e.g. with `ctx.stop()`) are not restarted.
+pub struct Supervisor {
+    events: Sender<Event>,
+    thread: JoinHandle<Result<(), SupervisorError>>,
+}
+
+impl Supervisor {
+    /// Starts the supervisor thread.
+    pub fn start(policy: Policy) -> Self {
+        let (events, rx) = channel();
+        let events_for_thread = events.clone();
+        let thread = thread::spawn(move || supervise(policy, rx, events_for_thread));
+        Supervisor { events, thread }
+    }
+
+    /// Starts an actor with `factory` (e.g. `|| Actor { ... }.start()`) and keeps it
+    /// running, calling `factory` again after every panic.
+    ///
+    /// If the supervisor has already given up, the actor is started but not supervised.
+    pub fn supervise<F, T, TX>(&self, factory: F) -> Supervised<TX>
+    where
+        F: Fn() -> Handle<T, TX> + Send + 'static,
+        T: JoinableHandle + Send + 'static,
+        TX: Send + 'static,
+    {
+        let handle = factory();
+        let current = Arc::new(Mutex::new(handle.addr()));
+        let child = Box::new(Factory {
+            factory,
+            current: current.clone(),
+        });
+        let _ = self.events.send(Event::Add(child, handle.into()));
+        Supervised { current }
+    }
+
+    /// Stops all supervised actors and waits (blocking) for them to stop.
+    ///
+    /// Returns `Err` if the supervisor gave up earlier because of too many restarts, or if
+    /// actors panicked while stopping.
+    pub fn stop(self) -> Result<(), SupervisorError> {
+        let _ = self.events.send(Event::Stop);
+        self.thread.join().expect("supervisor panicked")
+    }
+}
+
+/// Actor watched by a [`Supervisor`]. Keeps track of the address of the current
+/// incarnation of the actor.
+///
+/// [`Supervisor`]: struct.Supervisor.html
+pub struct Supervised<TX> {
+    current: Arc<Mutex<Addr<TX>>>,
+}
+
+impl<TX> Clone for Supervised<TX> {
+    fn clone(&self) -> Self {
+        Supervised {
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<TX> Supervised<TX> {
+    /// Returns address of the currently running incarnation of the actor.
+    ///
+    /// It stays valid until the actor is restarted.
+    pub fn addr(&self) -> Addr<TX> {
+        self.current.lock().unwrap().clone()
+    }
+    /// Sends a message to the current incarnation. Fails if the actor is not running,
+    /// e.g. if it panicked and is waiting to be restarted.
+    pub fn send(&self, msg: TX) -> Result<(), SendError<TX>> {
+        self.addr().send(msg)
+    }
+}
+
+enum Event {
+    Add(Box<dyn Child>, AnyHandle),
+    Exit {
+        id: usize,
+        incarnation: usize,
+        result: thread::Result<()>,
+    },
+    Stop,
+}
+
+/// Type-erased factory of an actor.
+trait Child: Send {
+    fn start(&self) -> AnyHandle;
+}
+
+struct Factory<F, TX> {
+    factory: F,
+    current: Arc<Mutex<Addr<TX>>>,
+}
+
+impl<F, T, TX> Child for Factory<F, TX>
+where
+    F: Fn() -> Handle<T, TX> + Send,
+    T: JoinableHandle + Send + 'static,
+    TX: Send + 'static,
+{
+    fn start(&self) -> AnyHandle {
+        let handle = (self.factory)();
+        *self.current.lock().unwrap() = handle.addr();
+        handle.into()
+    }
+}
+
+struct ChildState {
+    child: Box<dyn Child>,
+    stop: Option<Box<dyn Fn() + Send>>,
+    incarnation: usize,
+    restart_at: Option<Instant>,
+}
+
+/// Supervisor thread.
+fn supervise(
+    policy: Policy,
+    events: Receiver<Event>,
+    events_tx: Sender<Event>,
+) -> Result<(), SupervisorError> {
+    let mut children: Vec<ChildState> = Vec::new();
+    let mut restarts: VecDeque<Instant> = VecDeque::new();
+    let mut stopping = false;
+    let mut result = Ok(());
+    let mut panicked_while_stopping = Vec::new();
+
+    // Watches the incarnation in a separate thread, reporting when it exits.
+    let watch = |id: usize, incarnation: usize, running: AnyHandle| {
+        let AnyHandle { stop, join } = running;
+        let events = events_tx.clone();
+        thread::spawn(move || {
+            let result = join();
+            let _ = events.send(Event::Exit {
+                id,
+                incarnation,
+                result,
+            });
+        });
+        stop
+    };
+
+    loop {
+        let running = children.iter().filter(|c| c.stop.is_some()).count();
+        if stopping && running == 0 {
+            if result.is_ok() && !panicked_while_stopping.is_empty() {
+                return Err(SupervisorError::PanickedWhileStopping(
+                    panicked_while_stopping,
+                ));
+            }
+            return result;
+        }
+
+        // Children still running are restarted after they exit, not at `restart_at`
+        let next_restart = children
+            .iter()
+            .filter(|c| c.stop.is_none())
+            .filter_map(|c| c.restart_at)
+            .min();
+        let event = match next_restart {
+            Some(at) => events.recv_timeout(at.saturating_duration_since(Instant::now())),
+            None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Ok(Event::Add(child, running)) => {
+                let id = children.len();
+                let stop = watch(id, 0, running);
+                if stopping {
+                    stop();
+                }
+                children.push(ChildState {
+                    child,
+                    stop: Some(stop),
+                    incarnation: 0,
+                    restart_at: None,
+                });
+            }
+            Ok(Event::Stop) => {
+                stopping = true;
+                for child in &mut children {
+                    child.restart_at = None;
+                    if let Some(stop) = &child.stop {
+                        stop();
+                    }
+                }
+            }
+            Ok(Event::Exit {
+                id,
+                incarnation,
+                result: exit,
+            }) => {
+                if children[id].incarnation != incarnation {
+                    continue;
+                }
+                children[id].stop = None;
+                if exit.is_err() && stopping {
+                    panicked_while_stopping.push(id);
+                }
+                if exit.is_ok() || stopping {
+                    continue;
+                }
+
+                let now = Instant::now();
+                restarts.push_back(now);
+                while restarts.front().is_some_and(|&t| now - t > policy.window) {
+                    restarts.pop_front();
+                }
+                if restarts.len() > policy.max_restarts {
+                    // Give up
+                    result = Err(SupervisorError::TooManyRestarts);
+                    stopping = true;
+                    for child in &mut children {
+                        child.restart_at = None;
+                        if let Some(stop) = &child.stop {
+                            stop();
+                        }
+                    }
+                    continue;
+                }
+
+                let restart_at = now + policy.backoff.delay(restarts.len());
+                children[id].restart_at = Some(restart_at);
+                if policy.strategy == Strategy::OneForAll {
+                    for child in &mut children {
+                        if let Some(stop) = &child.stop {
+                            stop();
+                            child.restart_at = Some(restart_at);
+                        }
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                for (id, child) in children.iter_mut().enumerate() {
+                    // With one-for-all, wait for the stopped actors to exit first
+                    let due = child.restart_at.is_some_and(|at| at <= now);
+                    if due && child.stop.is_none() {
+                        child.restart_at = None;
+                        child.incarnation += 1;
+                        child.stop = Some(watch(id, child.incarnation, child.child.start()));
+                    }
+                }
+            }
+            // Can't happen, the supervisor holds a sender
+            Err(RecvTimeoutError::Disconnected) => return result,
+        }
+    }
+}
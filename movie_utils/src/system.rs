@@ -0,0 +1,132 @@
+//! Starting actors as a group and stopping them together.
+//!
+//! ```rust,ignore
+//! use movie::ActorSystem;
+//! use std::time::Duration;
+//!
+//! let mut system = ActorSystem::new();
+//! let db = system.add("db", DatabaseActor::Actor {}.start());
+//! // `api` depends on `db`, so it's stopped before it
+//! system.add("api", ApiActor::Actor { db }.start());
+//!
+//! let report = system.shutdown(Duration::from_secs(5));
+//! assert!(report.is_clean(), "not stopped: {:?}", report.timed_out);
+//! ```
+
+use crate::mailbox::Addr;
+use crate::{AnyHandle, Handle, JoinableHandle};
+
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time every actor gets to stop in [`ActorSystem::shutdown()`], even once the deadline
+/// has passed, so that one stuck actor doesn't make all the following ones time out.
+///
+/// [`ActorSystem::shutdown()`]: struct.ActorSystem.html#method.shutdown
+pub const STOP_GRACE: Duration = Duration::from_millis(100);
+
+/// Owns handles of a group of actors.
+///
+/// Actors are stopped in reverse order of adding them. An actor that uses another
+/// actor's address has to be started after it, so dependents are stopped before their
+/// dependencies.
+#[derive(Default)]
+pub struct ActorSystem {
+    actors: Vec<(String, AnyHandle)>,
+}
+
+impl ActorSystem {
+    /// Creates an empty system. Actors are added with [`add()`].
+    ///
+    /// [`add()`]: #method.add
+    pub fn new() -> Self {
+        ActorSystem::default()
+    }
+
+    /// Takes ownership of a started actor. Returns its address. `name` is used in
+    /// [`ShutdownReport`].
+    ///
+    /// [`ShutdownReport`]: struct.ShutdownReport.html
+    pub fn add<T, TX>(&mut self, name: impl Into<String>, handle: Handle<T, TX>) -> Addr<TX>
+    where
+        T: JoinableHandle + Send + 'static,
+        TX: Send + 'static,
+    {
+        let addr = handle.addr();
+        self.actors.push((name.into(), handle.into()));
+        addr
+    }
+
+    /// Takes ownership of an actor started with a type-erased handle.
+    pub(crate) fn push(&mut self, name: String, actor: AnyHandle) {
+        self.actors.push((name, actor));
+    }
+
+    /// Number of actors in the system.
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+
+    /// Asks all actors to stop, in reverse order of adding them, without waiting.
+    pub(crate) fn request_stop(&self) {
+        for (_, actor) in self.actors.iter().rev() {
+            (actor.stop)();
+        }
+    }
+
+    /// Stops all actors, one by one, in reverse order of adding them. Each actor is
+    /// asked to stop once the previous one has stopped (or panicked).
+    ///
+    /// Gives up waiting after `timeout` in total. Actors that are still running then are
+    /// asked to stop anyway, and reported in [`ShutdownReport::timed_out`]. Each actor
+    /// asked to stop after the deadline still gets [`STOP_GRACE`] to stop.
+    ///
+    /// [`STOP_GRACE`]: constant.STOP_GRACE.html
+    /// [`ShutdownReport::timed_out`]: struct.ShutdownReport.html#structfield.timed_out
+    pub fn shutdown(self, timeout: Duration) -> ShutdownReport {
+        let deadline = Instant::now() + timeout;
+        let mut report = ShutdownReport::default();
+        for (name, actor) in self.actors.into_iter().rev() {
+            (actor.stop)();
+            // Join in a separate thread, so that waiting for it can time out
+            let (tx, rx) = channel();
+            let join = actor.join;
+            thread::spawn(move || {
+                let _ = tx.send(join());
+            });
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(timeout.max(STOP_GRACE)) {
+                Ok(Ok(())) => report.stopped.push(name),
+                Ok(Err(_)) => report.panicked.push(name),
+                Err(_) => report.timed_out.push(name),
+            }
+        }
+        report
+    }
+}
+
+/// Result of [`ActorSystem::shutdown()`]. Lists actors' names in the order they were
+/// stopped.
+///
+/// [`ActorSystem::shutdown()`]: struct.ActorSystem.html#method.shutdown
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Actors that stopped normally.
+    pub stopped: Vec<String>,
+    /// Actors that panicked, before or while stopping.
+    pub panicked: Vec<String>,
+    /// Actors that did not stop in time. They may still be running.
+    pub timed_out: Vec<String>,
+}
+
+impl ShutdownReport {
+    /// Returns `true` if all actors stopped normally.
+    pub fn is_clean(&self) -> bool {
+        self.panicked.is_empty() && self.timed_out.is_empty()
+    }
+}
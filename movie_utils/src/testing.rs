@@ -0,0 +1,210 @@
+//! Running an actor deterministically in tests, on a virtual clock.
+//!
+//! ```rust,ignore
+//! use movie::TestActor;
+//! use std::time::Duration;
+//!
+//! // `tick_interval: 5`, `on_tick: self.lines_parsed += 1;`
+//! let mut actor = TestActor::new(ParserActor::Actor { lines_parsed: 0 });
+//! actor.step_ticks(20);
+//! assert_eq!(actor.actor().lines_parsed, 20);
+//! // 20 more ticks, without waiting
+//! actor.advance(Duration::from_millis(100));
+//! assert_eq!(actor.ask(ParserActor::Input::GetLinesParsed), 40);
+//!
+//! actor.send(ParserActor::Input::Reset).unwrap();
+//! assert_eq!(actor.actor().lines_parsed, 40); // not handled yet
+//! assert!(actor.deliver());
+//! assert_eq!(actor.actor().lines_parsed, 0);
+//! ```
+
+use crate::actor::{next_deadline, run_round, Actor, Event};
+use crate::context::Context;
+use crate::local_data::LocalData;
+use crate::mailbox::{Addr, Envelope, Mailbox, SendError};
+use crate::reply::ReplyTo;
+
+use std::time::{Duration, Instant};
+
+/// Actor run by the test itself, step by step, on a virtual clock. Nothing happens
+/// between the calls: messages are handled by [`deliver()`], ticks and timers run when
+/// the clock is moved with [`advance()`] or [`step_ticks()`].
+///
+/// The handlers are called directly, `Actor::run()` is not used, so actors whose handlers
+/// only work in `run()` (see `Actor::RUN_ONLY`) are rejected at compile time. `TestActor`
+/// keeps the actor's `local_data` between the calls, so it can't be sent to another thread.
+/// `ctx.now()` returns the virtual time, but
+/// `Instant::now()` and messages sent with `Addr::send_after()` use the real one. Ticks
+/// and timers must not have an interval of 0, as the clock would never move past them.
+///
+/// [`deliver()`]: #method.deliver
+/// [`advance()`]: #method.advance
+/// [`step_ticks()`]: #method.step_ticks
+pub struct TestActor<A: Actor> {
+    actor: A,
+    ctx: Context<A::Input>,
+    mailbox: Mailbox<A::Input>,
+    local: LocalData,
+}
+
+impl<A: Actor> TestActor<A> {
+    /// Runs `on_init`. The clock starts at the current time, and only moves when told to.
+    pub fn new(mut actor: A) -> Self {
+        const {
+            assert!(
+                !A::RUN_ONLY,
+                "the actor's handlers only work in `Actor::run()`, it can't be tested step by step"
+            );
+        }
+        let (addr, mailbox) = A::mailbox();
+        let addr_id = addr.id();
+        let mut ctx = Context::new(A::name(), addr);
+        ctx.virtual_now = Some(Instant::now());
+        ctx.set_tick_interval(A::TICK_INTERVAL);
+        ctx.set_tick_schedule(A::TICK_SCHEDULE);
+        let mut local = LocalData::new(addr_id);
+        local.enter(|| actor.on_init(&mut ctx));
+        TestActor {
+            actor,
+            ctx,
+            mailbox,
+            local,
+        }
+    }
+
+    /// The actor's data.
+    pub fn actor(&self) -> &A {
+        &self.actor
+    }
+
+    /// The actor's data, e.g. to set it up for a test.
+    pub fn actor_mut(&mut self) -> &mut A {
+        &mut self.actor
+    }
+
+    /// The actor's `ctx`, e.g. for `tick_count()` or `is_timer_running()`.
+    pub fn ctx(&self) -> &Context<A::Input> {
+        &self.ctx
+    }
+
+    /// Address of the actor. Messages sent to it wait for [`deliver()`].
+    ///
+    /// [`deliver()`]: #method.deliver
+    pub fn addr(&self) -> Addr<A::Input> {
+        self.ctx.addr().clone()
+    }
+
+    /// Time of the virtual clock.
+    pub fn now(&self) -> Instant {
+        self.ctx.now()
+    }
+
+    /// Puts the message in the mailbox, to be handled by [`deliver()`]. Never blocks,
+    /// like `Addr::try_send()`.
+    ///
+    /// [`deliver()`]: #method.deliver
+    pub fn send(&self, message: A::Input) -> Result<(), SendError<A::Input>> {
+        self.ctx.addr().try_send(message)
+    }
+
+    /// Handles the first waiting message (or stop request). Returns `false` if there was
+    /// none, or if the actor is stopping.
+    pub fn deliver(&mut self) -> bool {
+        if self.ctx.is_stopping() {
+            return false;
+        }
+        match self.mailbox.recv_timeout(Duration::from_secs(0)) {
+            Ok(Envelope::Message(message)) => {
+                let (actor, ctx) = (&mut self.actor, &mut self.ctx);
+                self.local.enter(|| actor.on_message(ctx, message))
+            }
+            Ok(Envelope::Stop) => self.ctx.stop(),
+            Err(_) => return false,
+        }
+        true
+    }
+
+    /// Handles the waiting messages, including the ones sent while handling them.
+    /// Returns how many were handled.
+    pub fn deliver_all(&mut self) -> usize {
+        let mut delivered = 0;
+        while self.deliver() {
+            delivered += 1;
+        }
+        delivered
+    }
+
+    /// Sends the message and handles the waiting messages up to it, returning the reply.
+    ///
+    /// # Panics
+    ///
+    /// If the actor doesn't reply, or the mailbox is full.
+    pub fn ask<R>(&mut self, message: impl FnOnce(ReplyTo<R>) -> A::Input) -> R {
+        let (reply, pending) = ReplyTo::channel();
+        if self.send(message(reply)).is_err() {
+            panic!("mailbox of {} is full", A::name());
+        }
+        loop {
+            match pending.try_wait() {
+                Ok(Some(reply)) => return reply,
+                Ok(None) if self.deliver() => (),
+                _ => panic!("{} did not reply", A::name()),
+            }
+        }
+    }
+
+    /// Moves the clock forward by `duration`, running ticks and timers in order, each at
+    /// the time it's due. Waiting messages are not handled.
+    pub fn advance(&mut self, duration: Duration) {
+        let until = self.now() + duration;
+        self.advance_to(until);
+    }
+
+    /// Moves the clock to the next tick `ticks` times, running the tick and the timers
+    /// due before it. Stops early if the actor is stopping.
+    pub fn step_ticks(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            if self.ctx.is_stopping() {
+                return;
+            }
+            let tick = self.ctx.next_tick.max(self.now());
+            self.advance_to(tick);
+        }
+    }
+
+    /// Runs `on_stop` and returns its value. Waiting messages are dropped.
+    pub fn stop(self) -> A::Output {
+        let TestActor {
+            actor,
+            mut ctx,
+            mailbox,
+            mut local,
+        } = self;
+        let output = local.enter(|| actor.on_stop(&mut ctx));
+        // `mailbox` is dropped after `on_stop`, like in `Actor::run()`
+        drop(mailbox);
+        output
+    }
+
+    fn advance_to(&mut self, until: Instant) {
+        let actor = &mut self.actor;
+        let ctx = &mut self.ctx;
+        while !ctx.is_stopping() {
+            let deadline = next_deadline(ctx);
+            if deadline > until {
+                break;
+            }
+            ctx.virtual_now = Some(deadline.max(ctx.now()));
+            self.local.enter(|| {
+                run_round(ctx, None, &mut |ctx, event| match event {
+                    Event::Message(message) => actor.on_message(ctx, message),
+                    Event::Tick => actor.on_tick(ctx),
+                    Event::Timer(timer) => actor.on_timer(ctx, timer),
+                })
+            });
+        }
+        if until > ctx.now() {
+            ctx.virtual_now = Some(until);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Named timers of an actor, controlled through `Context`.
+
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+pub(crate) struct Timers {
+    timers: Vec<Timer>,
+}
+
+struct Timer {
+    name: &'static str,
+    interval: Duration,
+    state: State,
+}
+
+enum State {
+    /// Fires at the given time.
+    Running(Instant),
+    /// Paused with the given time left.
+    Paused(Duration),
+    /// Its handler is running. Scheduled again once it returns, unless the handler
+    /// changed the timer.
+    Firing,
+}
+
+impl Timers {
+    /// Starts a timer, replacing the one with the same name.
+    pub fn start(&mut self, name: &'static str, interval: Duration, now: Instant) {
+        self.cancel(name);
+        self.timers.push(Timer {
+            name,
+            interval,
+            state: State::Running(now + interval),
+        });
+    }
+
+    /// Changes the interval. The timer fires `interval` from now, or from resuming.
+    pub fn set_interval(&mut self, name: &'static str, interval: Duration, now: Instant) -> bool {
+        self.with(name, |timer| {
+            timer.interval = interval;
+            timer.state = match timer.state {
+                State::Paused(_) => State::Paused(interval),
+                _ => State::Running(now + interval),
+            };
+        })
+    }
+
+    pub fn pause(&mut self, name: &'static str, now: Instant) -> bool {
+        self.with(name, |timer| {
+            let left = match timer.state {
+                State::Running(at) => at.saturating_duration_since(now),
+                State::Paused(left) => left,
+                State::Firing => timer.interval,
+            };
+            timer.state = State::Paused(left);
+        })
+    }
+
+    pub fn resume(&mut self, name: &'static str, now: Instant) -> bool {
+        self.with(name, |timer| {
+            if let State::Paused(left) = timer.state {
+                timer.state = State::Running(now + left);
+            }
+        })
+    }
+
+    pub fn cancel(&mut self, name: &'static str) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.name != name);
+        self.timers.len() != len
+    }
+
+    pub fn interval(&self, name: &str) -> Option<Duration> {
+        self.find(name).map(|timer| timer.interval)
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.find(name)
+            .is_some_and(|timer| !matches!(timer.state, State::Paused(_)))
+    }
+
+    /// When the next timer fires, `None` if none is running.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers
+            .iter()
+            .filter_map(|timer| match timer.state {
+                State::Running(at) => Some(at),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// Takes the timer that should have fired the earliest, if any is due at `now`.
+    /// [`fired()`] has to be called after running its handler.
+    ///
+    /// [`fired()`]: #method.fired
+    pub fn take_due(&mut self, now: Instant) -> Option<&'static str> {
+        let timer = self
+            .timers
+            .iter_mut()
+            .filter(|timer| matches!(timer.state, State::Running(at) if at <= now))
+            .min_by_key(|timer| match timer.state {
+                State::Running(at) => at,
+                _ => unreachable!(),
+            })?;
+        timer.state = State::Firing;
+        Some(timer.name)
+    }
+
+    /// Schedules the timer again, `interval` after its handler returned (at `now`).
+    pub fn fired(&mut self, name: &'static str, now: Instant) {
+        self.with(name, |timer| {
+            if let State::Firing = timer.state {
+                timer.state = State::Running(now + timer.interval);
+            }
+        });
+    }
+
+    fn find(&self, name: &str) -> Option<&Timer> {
+        self.timers.iter().find(|timer| timer.name == name)
+    }
+
+    fn with(&mut self, name: &str, f: impl FnOnce(&mut Timer)) -> bool {
+        match self.timers.iter_mut().find(|timer| timer.name == name) {
+            Some(timer) => {
+                f(timer);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -11,19 +11,44 @@
 //! - next to no boilerplate - see examples
 //! - works with `stable` compiler, but requires 2018 edition
 //! - no external dependencies except for `std`
-//! - enum-based communication over MPSC channels
-//! - by default, one actor = one thread
-//! - by default, actors only accept messages, they do not send replies
-//!   - solution to sending replies is not the most elegant right now,
-//!     see [Advanced example](#advanced-example) below
+//! - enum-based communication over MPSC channels, optionally bounded
+//! - by default, one actor = one thread. `movie::ThreadPool::new(n)` runs any number of
+//!   actors on `n` threads instead: `pool.start(actor)` returns a `Handle` like `start()`
+//!   does, and actors with messages waiting take turns
+//! - actors can also run on the current thread (e.g. the GUI's main thread):
+//!   `actor.run_here()` blocks until the actor stops, `actor.run_with_handle(|handle| ..)`
+//!   first gives its handle away, and `movie::Pump::new(actor)` is run in steps with
+//!   `pump()`, from an existing event loop
+//! - actors can reply to messages - declare reply-bearing `input` variants and `ask`,
+//!   see [Advanced example](#advanced-example) below
+//! - actors can publish events (`output`) to any number of subscribers
+//! - `movie::Pipeline` connects actors' events to other actors' input through mapping
+//!   functions, starts them in dependency order and returns an `ActorSystem`, which stops
+//!   them in reverse order
+//! - `Handle` owns the actor, `handle.addr()` returns a cloneable `Addr` that other
+//!   threads and actors can use to send messages and check if the actor is alive
+//! - `handle.stop_timeout(duration)` gives up on actors stuck in a handler and gives the
+//!   handle back, `request_stop()` and `is_finished()` don't block at all
+//! - `addr.send_after(delay, msg)`, `send_at(instant, msg)` and
+//!   `send_interval(period, || msg)` deliver messages later or repeatedly, from one shared
+//!   timer thread, and return a `Scheduled` that cancels them
+//! - `movie::ActorSystem` owns handles of a group of actors and stops them in reverse
+//!   order, reporting which ones did not stop in time
+//! - actors that panic can be restarted with fresh data by a `movie::supervisor::Supervisor`
+//!   (one-for-one or one-for-all, with a restart limit and backoff)
+//! - tests don't have to sleep: `movie::TestActor::new(actor)` runs the actor on a virtual
+//!   clock, without threads. `advance(duration)` and `step_ticks(n)` move the clock,
+//!   running ticks and timers, `deliver()` handles one waiting message, and `actor()`
+//!   gives access to the data for assertions
 //! - network RPC should be possible but is beyond the scope of this crate.
 //!   If you want to do this, you can use `input_derive` and `custom_code` to
 //!   derive `Serialize` and `Deserialize`.
-//! - two procedural macros - see [`movie_derive`]
+//! - `actor!` macro (and `actor_dbg!`), or `#[actor]` attribute on plain Rust code - see
+//!   [`movie_derive`] and [Attribute form](#attribute-form)
+//! - both generate an implementation of the `movie::Actor` trait, which can also be
+//!   implemented by hand - see [Without macros](#without-macros)
 //! - actors need to be defined in module/crate scope
-//! - bad error messages for now, macro + manual string parsing magic
-//! - in case of large breaking changes in (stable) `TokenStream::to_string()`,
-//!   the macros may break
+//! - misspelled, duplicate or misplaced attributes are reported at the offending token
 //!
 //! ## Examples
 //!
@@ -57,8 +82,8 @@
 //!     // Create and spawn the actor
 //!     let actor = Actor {}.start();
 //!
-//!     actor.send(Input::Ping);
-//!     actor.stop(); // Will block, waiting for actor.
+//!     actor.send(Input::Ping).unwrap();
+//!     actor.stop().unwrap(); // Will block, waiting for actor.
 //! }
 //! ```
 //!
@@ -67,7 +92,6 @@
 //! ```rust,ignore
 //! use movie::actor;
 //!
-//! use std::sync::mpsc::Sender;
 //! actor! {
 //!     StreamParsingActor
 //!         public_visibility: true,
@@ -75,16 +99,14 @@
 //!               /// It's very consistent - failed every time so far.
 //!         input:
 //!             ChangeSource(String),
-//!             SendState,
+//!             // Reply-bearing variant, gets `movie::ReplyTo<(String, u64)>` as its last field
+//!             GetState -> (String, u64),
 //!         // By default, Input enum does not have any trait auto-implemented.
-//!         input_derive: Debug, PartialEq,
+//!         input_derive: Debug,
 //!         // Whitespace and comments are irrelevant.
-//!         // It's also optional to end sections (attributes) with a comma, with
-//!         // exception of code attributes (on_stop, on_init etc.), which should
-//!         // not end with comma, but rather either with nothing or with a semicolon.
+//!         // It's also optional to end sections (attributes) with a comma.
 //!         data:
 //!             pub device: String,
-//!             pub state_tx: Sender<u64>,
 //!         on_init:
 //!             if self.device == "admin secret device" {
 //!                 panic!("No access right for admin secret device");
@@ -98,27 +120,28 @@
 //!             ChangeSource(name) => {
 //!                 self.device = name;
 //!             },
-//!             SendState => {
-//!                 self.state_tx.send(lines_parsed).unwrap();
+//!             GetState(reply) => {
+//!                 reply.send((self.device.clone(), lines_parsed));
 //!             }
 //!         tick_interval: 5, // Every 5ms, default = 100
+//!         // Ticks every 5ms even if on_tick takes time, default = FixedDelay
+//!         tick_schedule: CatchUp,
 //!         on_tick: // on_message have priority over on_tick
 //!             lines_parsed += 1;
-//!         on_stop: ()
+//!         // Type of the value returned by Handle::stop(), default = ()
+//!         returns: u64,
+//!         on_stop: lines_parsed,
 //!         // custom_code must end with a semicolon
 //!         custom_code:
-//!             pub const DEFAULT_DEVICE: &'static str = "video0";
+//!             pub const DEFAULT_DEVICE: &str = "video0";
 //! }
 //!
 //! #[test]
 //! fn test_stream_parsing_actor() {
 //!     use StreamParsingActor::{Actor, Input, DEFAULT_DEVICE};
 //!
-//!     use std::sync::mpsc::channel;
-//!     let (tx, rx) = channel();
 //!     let cfg = Actor {
 //!         device: DEFAULT_DEVICE.to_string(),
-//!         state_tx: tx,
 //!     };
 //!     // Spawn the actor, let on_init run
 //!     let actor = cfg.start(); // returns StreamParsingActor::Handle
@@ -128,10 +151,15 @@
 //!     sleep(Duration::from_millis(100));
 //!
 //!     // We can use auto-derived traits on Input
-//!     actor.send(dbg!(Input::SendState));
-//!     println!("Ticked {} times in 100ms", rx.recv().unwrap()); // 20
-//!
-//!     actor.stop();
+//!     actor.send(dbg!(Input::ChangeSource("video1".to_string()))).unwrap();
+//!     // Send GetState and wait for the reply
+//!     let (device, ticks) = actor.ask(Input::GetState).unwrap();
+//!     assert_eq!(device, "video1");
+//!     println!("Ticked {} times in 100ms", ticks); // 20
+//!
+//!     // on_stop's value is returned
+//!     let lines_parsed = actor.stop().unwrap();
+//!     assert!(lines_parsed >= ticks);
 //! }
 //! ```
 //!
@@ -141,30 +169,204 @@
 //!
 //! ## Actor attributes
 //!
-//! These words if followed by colon, are restricted keywords.
-//!
-//! - `input` - defines `Input` enum
+//! These words if followed by colon, are restricted keywords. The only exceptions are
+//! bindings, fields and closure parameters, e.g. `let data: u32`, `pub input: u32` or
+//! `|data: u32| data + 1`.
+//!
+//! - `input` - defines `Input` enum. `Variant -> Type` (or `Variant(fields) -> Type`)
+//!   declares a reply-bearing variant. It gets `movie::ReplyTo<Type>` as its last field, the
+//!   actor answers with `reply.send(value)` and the owner gets the value from `Handle::ask`
+//!   (or `ask_timeout`, `ask_deferred`). Every variant also gets a snake_case method in
+//!   `HandleExt` trait, implemented for the handle and `Addr`: `handle.change_source(name)`
+//!   sends `ChangeSource(name)`, `handle.get_state()` asks `GetState` and returns the reply.
+//!   `HTTPRequest` gets `http_request()`, keywords are raw (`Move` gets `r#move()`), and
+//!   variants named like methods of the handle (`Send`, `Stop`, `Id`, `Clone`, ...) are rejected.
 //! - `input_derive` - `#[derive()]` for `Input` enum
+//! - `output` - defines `Output` enum, for events the actor emits with
+//!   `ctx.emit(Output::Started)`. Anyone with a handle or `Addr` can get a `Receiver` of
+//!   them with `subscribe()`, e.g. `let events: Receiver<Output> = handle.subscribe();`.
+//!   `Input` implements `movie::HasOutput`, so other types can't be emitted or subscribed to.
+//!   Every subscriber gets a copy of every event emitted after it subscribed. Dropped
+//!   receivers are unsubscribed, and receivers disconnect when the actor stops.
+//! - `output_derive` - `#[derive()]` for `Output` enum, in addition to `Clone`
 //! - `data` - actor stateful variables, need to be set when creating actor
-//! - `on_init` - runs just before an actor starts accepting messages
+//! - `local_data` - actor state built on its thread when it starts, written like fields
+//!   with initial values: `lines_parsed: u64 = 0, history: Rc<Vec<String>> = Rc::default()`.
+//!   Handlers use it as `local.lines_parsed`. Unlike `data`, it is never sent between
+//!   threads, so it can be `!Send` (e.g. `Rc` or GTK widgets). Initial values can use
+//!   `self` and `ctx`, and variables declared in `on_init` stay local to it. Actors with
+//!   `local_data` can't be generic, and can't be run by `ThreadPool`, which moves actors
+//!   between threads (this is checked at compile time). `Pump` and `TestActor` keep it
+//!   between the calls, so they can't be sent to another thread.
+//! - `on_init` - runs just before an actor starts accepting messages. Variables declared
+//!   in it are visible in the other handlers (without `local_data`), but then the actor can
+//!   only be run in its own thread, not by `ThreadPool`, `Pump` or `TestActor` (this is
+//!   checked at compile time)
 //! - `on_message` - defines `match message` logic
 //! - `tick_interval` - time in milliseconds between tick. When undefined, set to 100ms.
-//!    Affects message polling, so don't set it too high.
+//!   Messages are handled as soon as they arrive, regardless of this value.
+//! - `tick_schedule` - `FixedDelay` (default) waits `tick_interval` after `on_tick`
+//!   returns, so the period is `tick_interval` plus time spent handling. `CatchUp` and
+//!   `Skip` tick at a fixed rate, every `tick_interval`: when ticks are late, `CatchUp`
+//!   runs them back to back, `Skip` skips them. `ctx.missed_ticks()` returns how many
+//!   ticks the current one is late by.
+//! - `mailbox_capacity` - maximum number of messages waiting to be handled. When
+//!   undefined, the mailbox is unbounded. `Handle::queue_len()` returns the current number.
+//! - `mailbox_overflow` - what `send` does when the mailbox is full: `Block` (default)
+//!   waits, `Fail` returns `SendError::Full` with the message, `DropNewest` drops the
+//!   message, `DropOldest` drops the oldest waiting message. `try_send` never blocks.
 //! - `on_tick` - runs every tick
+//! - `timers` - named timers, each with its own interval and handler, written like match
+//!   arms: `report(1000) => self.report(),` or `flush(Duration::from_secs(5)) => { ... }`.
+//!   The interval is in milliseconds, or a `Duration` expression. Timers start just before
+//!   `on_init`, and fire `interval` after their handler last returned.
 //! - `on_stop` - runs just after an actor stops accepting messages
+//! - `returns` - type of the value of `on_stop`, which is then returned by `Handle::stop()`
+//!   and `Handle::join()` (e.g. `returns: u64, on_stop: lines_parsed`, or `returns: Actor,
+//!   on_stop: self` to get `data` back). When undefined, `on_stop`'s value is ignored.
 //! - `spawner` - name of the function that spawns thread (by default
 //!   `std::thread::spawn`, put a function with similar signature here to have actors be run
-//!   as futures, M:N threads etc.)
+//!   as futures etc.). Spawned functions run the actor until it stops, so to share threads
+//!   between actors, start them with `movie::ThreadPool` instead. Without `spawner`,
+//!   `start()` panics if the thread can't be spawned, and `try_start()` returns the error.
 //! - `spawner_return_type` - return type of `spawner` (by default
-//!   `std::thread::JoinHandle<()>`)
+//!   `std::thread::JoinHandle<Returns>`), needs to implement `movie::JoinableHandle`
+//!   with `Output` being the `returns` type
+//! - `stack_size` - stack size of the actor's thread in bytes. When undefined, Rust's
+//!   default is used.
+//! - `instance_id` - expression added to the name of the actor's thread, e.g. `instance_id:
+//!   self.port` names it `ListenerActor-8080`. When undefined, the thread is named after
+//!   the actor (`ListenerActor`), which shows up in panic messages, debuggers and `top -H`.
 //! - `custom_code` - code to be inserted into generated actor module
 //! - `public_visibility` - if `true`, then the actor module is public
 //! - `docs` - place docs here - e.g. `docs: /// An actor`
 //!
-//! Some code can break macro internals (e.g. `break` or `continue` without
-//! defining your own loop can break actor's main loop, putting `on_stop: (),` will result
-//! in an invalid comma). Debugging it can be cryptic, hopefully [`actor_dbg`] (when the code
-//! doesn't compile) and [`cargo-expand`] (when it does) will help you in such situations.
+//! Code in `on_init`, `on_message`, `on_tick`, `timers` and `on_stop` can use `ctx`
+//! ([`Context`]) - `ctx.addr()` returns actor's own address, `ctx.stop()` stops the actor
+//! once the current handler returns, `ctx.tick_count()` and `ctx.name()` return number of
+//! ticks so far and actor's name, `ctx.emit(event)` sends an event to subscribers.
+//! `ctx.now()` returns the current time, virtual in a `TestActor`.
+//! `ctx.set_tick_interval(interval)` changes the tick interval, and
+//! `ctx.start_timer(name, interval)`, `set_timer_interval`, `pause_timer`, `resume_timer`
+//! and `cancel_timer` control timers, also ones not declared in `timers`.
+//!
+//! Attributes can be separated with commas, also after code attributes (e.g. `on_stop: (),`).
+//!
+//! Type and lifetime parameters, with bounds, defaults and a where clause, can follow the
+//! actor's name, e.g. `actor! { StreamParsingActor<S: Sink = Stdout> where S: Send + 'static
+//! ... }`. They are applied to `Actor`, `Input` and `Handle` (`Handle<S>`). `data` has to
+//! use every parameter, `Input` doesn't. `start()` needs `Actor` to be `Send + 'static`;
+//! actors borrowing data can be started in a scope with
+//! `movie::start_with(actor, |run| scope.spawn(run))`.
+//!
+//! `on_message`, `on_tick`, `timers` and `on_stop` are run inside closures, so `return`
+//! leaves the handler, and `break` or `continue` outside of your own loop don't compile.
+//! Use `ctx.stop()` to stop the actor. If the generated code does not work as expected,
+//! hopefully [`actor_dbg`] (when the code doesn't compile) and [`cargo-expand`] (when it
+//! does) will help you.
+//!
+//! ## Attribute form
+//!
+//! Instead of `actor!`, an actor can be written as a plain struct (`data`) and an impl
+//! block (handlers), both marked with `#[actor]`. It works with rustfmt and IDEs, and there
+//! are no rules about commas and semicolons.
+//!
+//! ```rust,ignore
+//! use movie::attr::actor;
+//! use movie::Context;
+//!
+//! #[actor(tick_interval = 5)]
+//! pub struct StreamParsingActor {
+//!     pub device: String,
+//!     pub lines_parsed: u64,
+//! }
+//!
+//! #[actor(input_derive(Debug))]
+//! impl StreamParsingActor {
+//!     fn on_init(&mut self) {}
+//!     /// Becomes `StreamParsingActorInput::ChangeSource(String)`
+//!     pub fn change_source(&mut self, name: String) {
+//!         self.device = name;
+//!     }
+//!     /// Reply-bearing: `StreamParsingActorInput::GetState(movie::ReplyTo<(String, u64)>)`
+//!     pub fn get_state(&self) -> (String, u64) {
+//!         (self.device.clone(), self.lines_parsed)
+//!     }
+//!     fn on_tick(&mut self, ctx: &mut Context<StreamParsingActorInput>) {
+//!         self.lines_parsed += 1;
+//!     }
+//!     fn on_stop(self) -> u64 {
+//!         self.lines_parsed
+//!     }
+//! }
+//!
+//! let actor: StreamParsingActorHandle = StreamParsingActor {
+//!     device: "video0".to_string(),
+//!     lines_parsed: 0,
+//! }
+//! .start();
+//! actor.send(StreamParsingActorInput::ChangeSource("video1".to_string())).unwrap();
+//! let (device, lines_parsed) = actor.ask(StreamParsingActorInput::GetState).unwrap();
+//! let lines_parsed: u64 = actor.stop().unwrap();
+//! ```
+//!
+//! - `#[actor(...)]` on the struct generates `start()`, `try_start()` and `NameHandle`. It
+//!   accepts `tick_interval`, `tick_schedule`, `mailbox_capacity`, `mailbox_overflow`,
+//!   `spawner`, `spawner_return_type`, `stack_size` and `instance_id`, written as `name =
+//!   value` and checked like the attributes of `actor!` (e.g. `mailbox_capacity` must be an
+//!   integer literal).
+//! - `#[actor(...)]` on the impl block generates `NameInput` enum, with a variant for every
+//!   method taking `self`, except for `on_init`, `on_tick`, `on_timer` and `on_stop`.
+//!   Arguments become variant fields, a return value makes the variant reply-bearing.
+//!   `NameInput` is as visible as the struct. It accepts `input_derive(...)`.
+//! - `NameHandleExt` trait has methods named like the handlers, sending their variants:
+//!   `actor.change_source(name)`, `actor.get_state()`.
+//! - every method can take `ctx` as the first argument after `self`. `on_stop` can take
+//!   `self` by value, its return value is returned by `Handle::stop()`. `on_timer` takes
+//!   the name of the timer that fired (`timer: &'static str`), timers are started with
+//!   `ctx.start_timer()`.
+//! - methods that should not be handlers (e.g. constructors) go in a separate impl block.
+//! - the struct has to come before its impl block.
+//!
+//! ## Without macros
+//!
+//! Actors are types implementing `movie::Actor`. Only `on_message` and `on_stop` are
+//! required, the other methods, `TICK_INTERVAL` and `TICK_SCHEDULE` have defaults.
+//! Actors emitting events implement `movie::HasOutput` for their input.
+//! `movie::start(actor)` spawns a thread, `movie::start_with(actor, spawner)` uses a custom
+//! spawner.
+//!
+//! ```rust,ignore
+//! use movie::{Actor, Context, ReplyTo};
+//!
+//! pub struct Counter {
+//!     count: u32,
+//! }
+//!
+//! pub enum CounterInput {
+//!     Increment,
+//!     Get(ReplyTo<u32>),
+//! }
+//!
+//! impl Actor for Counter {
+//!     type Input = CounterInput;
+//!     type Output = u32;
+//!     fn on_message(&mut self, _ctx: &mut Context<CounterInput>, message: CounterInput) {
+//!         match message {
+//!             CounterInput::Increment => self.count += 1,
+//!             CounterInput::Get(reply) => reply.send(self.count),
+//!         }
+//!     }
+//!     fn on_stop(self, _ctx: &mut Context<CounterInput>) -> u32 {
+//!         self.count
+//!     }
+//! }
+//!
+//! let counter = movie::start(Counter { count: 0 });
+//! counter.send(CounterInput::Increment).unwrap();
+//! assert_eq!(counter.ask(CounterInput::Get).unwrap(), 1);
+//! assert_eq!(counter.stop().unwrap(), 1);
+//! ```
 //!
 //! ## History
 //!
@@ -226,6 +428,7 @@
 //!
 //! [`movie_derive`]: https://movie.pzmarzly.pl/movie_derive/index.html
 //! [`movie_example`]: https://movie.pzmarzly.pl/movie_example/index.html
+//! [`Context`]: https://movie.pzmarzly.pl/movie_utils/context/struct.Context.html
 //! [`actor_dbg`]: https://movie.pzmarzly.pl/movie_derive/macro.actor_dbg.html
 //! [`cargo-expand`]: https://github.com/dtolnay/cargo-expand
 //! [`x11-input-supercharger`]: https://github.com/pzmarzly/x11-input-supercharger
@@ -242,3 +445,9 @@
 
 pub use movie_derive::*;
 pub use movie_utils::*;
+
+/// Attribute form of [`actor!`](macro.actor.html), see
+/// [Attribute form](index.html#attribute-form).
+pub mod attr {
+    pub use movie_derive::actor_attribute as actor;
+}
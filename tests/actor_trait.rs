@@ -0,0 +1,88 @@
+use movie::{actor, Actor, Context, Handle, JoinableHandle, ReplyTo};
+
+use std::time::Duration;
+
+pub struct Counter {
+    count: u32,
+    ticks: u64,
+}
+
+pub enum CounterInput {
+    Increment,
+    Get(ReplyTo<u32>),
+}
+
+impl Actor for Counter {
+    type Input = CounterInput;
+    type Output = (u32, u64);
+    const TICK_INTERVAL: Duration = Duration::from_millis(1);
+
+    fn on_message(&mut self, _ctx: &mut Context<CounterInput>, message: CounterInput) {
+        match message {
+            CounterInput::Increment => self.count += 1,
+            CounterInput::Get(reply) => reply.send(self.count),
+        }
+    }
+    fn on_tick(&mut self, ctx: &mut Context<CounterInput>) {
+        self.ticks = ctx.tick_count();
+    }
+    fn on_stop(self, _ctx: &mut Context<CounterInput>) -> (u32, u64) {
+        (self.count, self.ticks)
+    }
+}
+
+actor! {
+    MacroCounter
+        input: Increment, Get -> u32,
+        data:
+            pub count: u32,
+        on_message:
+            Increment => self.count += 1,
+            Get(reply) => reply.send(self.count),
+}
+
+/// Works with any actor that has `Increment` and `Get` messages.
+fn increment_twice<T: JoinableHandle, TX>(
+    actor: &Handle<T, TX>,
+    increment: impl Fn() -> TX,
+    get: impl FnOnce(ReplyTo<u32>) -> TX,
+) -> u32 {
+    actor.send(increment()).unwrap();
+    actor.send(increment()).unwrap();
+    actor.ask(get).unwrap()
+}
+
+#[test]
+fn test_hand_written_actor() {
+    let actor = movie::start(Counter { count: 0, ticks: 0 });
+    actor.send(CounterInput::Increment).unwrap();
+    assert_eq!(actor.ask(CounterInput::Get).unwrap(), 1);
+    std::thread::sleep(Duration::from_millis(20));
+    let (count, ticks) = actor.stop().unwrap();
+    assert_eq!(count, 1);
+    assert!(ticks > 0);
+}
+
+#[test]
+fn test_hand_written_actor_with_spawner() {
+    let actor = movie::start_with(Counter { count: 0, ticks: 0 }, |run| {
+        std::thread::Builder::new()
+            .name("counter".to_string())
+            .spawn(run)
+            .unwrap()
+    });
+    let count = increment_twice(&actor, || CounterInput::Increment, CounterInput::Get);
+    assert_eq!(count, 2);
+    assert_eq!(actor.stop().unwrap().0, 2);
+}
+
+#[test]
+fn test_macro_actor_implements_trait() {
+    use MacroCounter::{Actor, Input};
+    assert_eq!(<Actor as movie::Actor>::name(), "MacroCounter");
+    // Started like a hand-written actor
+    let actor = movie::start(Actor { count: 0 });
+    let count = increment_twice(&actor, || Input::Increment, Input::Get);
+    assert_eq!(count, 2);
+    actor.stop().unwrap();
+}
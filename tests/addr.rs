@@ -0,0 +1,54 @@
+use movie::actor;
+
+actor! {
+    CounterActor
+        input:
+            Increment,
+            Get -> u32,
+        data:
+            pub count: u32,
+        on_message:
+            Increment => self.count += 1,
+            Get(reply) => reply.send(self.count),
+}
+
+actor! {
+    ForwardingActor
+        input: Forward,
+        data:
+            pub counter: movie::Addr<CounterActor::Input>,
+        on_message:
+            Forward => self.counter.send(CounterActor::Input::Increment).unwrap(),
+}
+
+#[test]
+fn test_actors_share_address() {
+    let counter = CounterActor::Actor { count: 0 }.start();
+    let forwarder = ForwardingActor::Actor {
+        counter: counter.addr(),
+    }
+    .start();
+
+    forwarder.send(ForwardingActor::Input::Forward).unwrap();
+    forwarder.send(ForwardingActor::Input::Forward).unwrap();
+    forwarder.stop().unwrap();
+
+    let addr = counter.addr();
+    std::thread::spawn(move || addr.send(CounterActor::Input::Increment).unwrap())
+        .join()
+        .unwrap();
+
+    assert_eq!(counter.ask(CounterActor::Input::Get), Ok(3));
+    counter.stop().unwrap();
+}
+
+#[test]
+fn test_address_outlives_actor() {
+    let counter = CounterActor::Actor { count: 0 }.start();
+    let addr = counter.addr();
+    assert!(addr.is_alive());
+
+    counter.stop().unwrap();
+    assert!(!addr.is_alive());
+    assert!(addr.send(CounterActor::Input::Increment).is_err());
+}
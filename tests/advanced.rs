@@ -1,3 +1,5 @@
+#![allow(clippy::redundant_static_lifetimes)]
+
 use movie::actor;
 
 use std::sync::mpsc::Sender;
@@ -61,8 +63,8 @@ fn test_stream_parsing_actor() {
     sleep(Duration::from_millis(100));
 
     // We can use auto-derived traits on Input
-    actor.send(dbg!(Input::SendState));
+    actor.send(dbg!(Input::SendState)).unwrap();
     println!("Ticked {} times in 100ms", rx.recv().unwrap()); // 20
 
-    actor.stop();
+    actor.stop().unwrap();
 }
@@ -0,0 +1,73 @@
+use movie::actor;
+
+actor! {
+    StreamParsingActor
+        public_visibility: true,
+        docs: /// Actor that parses video from V4L2 device
+              /// It's very consistent - failed every time so far.
+        input:
+            ChangeSource(String),
+            // Reply-bearing variant, gets `movie::ReplyTo<(String, u64)>` as its last field
+            GetState -> (String, u64),
+        // By default, Input enum does not have any trait auto-implemented.
+        input_derive: Debug,
+        // Whitespace and comments are irrelevant.
+        // It's also optional to end sections (attributes) with a comma.
+        data:
+            pub device: String,
+        on_init:
+            if self.device == "admin secret device" {
+                panic!("No access right for admin secret device");
+            }
+            let mut lines_parsed = 0; // This variable will be exposed to on_message.
+                                      // This is suboptimal, but it is the simplest
+                                      // way to allow for thread-local variables (`data`
+                                      // is sent between threads, so it couldn't be used
+                                      // e.g. for GTK references)
+        on_message:
+            ChangeSource(name) => {
+                self.device = name;
+            },
+            GetState(reply) => {
+                reply.send((self.device.clone(), lines_parsed));
+            }
+        tick_interval: 5, // Every 5ms, default = 100
+        // Ticks every 5ms even if on_tick takes time, default = FixedDelay
+        tick_schedule: CatchUp,
+        on_tick: // on_message have priority over on_tick
+            lines_parsed += 1;
+        // Type of the value returned by Handle::stop(), default = ()
+        returns: u64,
+        on_stop: lines_parsed,
+        // custom_code must end with a semicolon
+        custom_code:
+            pub const DEFAULT_DEVICE: &str = "video0";
+}
+
+#[test]
+fn test_stream_parsing_actor_ask() {
+    use StreamParsingActor::{Actor, Input, DEFAULT_DEVICE};
+
+    let cfg = Actor {
+        device: DEFAULT_DEVICE.to_string(),
+    };
+    // Spawn the actor, let on_init run
+    let actor = cfg.start(); // returns StreamParsingActor::Handle
+
+    use std::thread::sleep;
+    use std::time::Duration;
+    sleep(Duration::from_millis(100));
+
+    // We can use auto-derived traits on Input
+    actor
+        .send(dbg!(Input::ChangeSource("video1".to_string())))
+        .unwrap();
+    // Send GetState and wait for the reply
+    let (device, ticks) = actor.ask(Input::GetState).unwrap();
+    assert_eq!(device, "video1");
+    println!("Ticked {} times in 100ms", ticks); // 20
+
+    // on_stop's value is returned
+    let lines_parsed = actor.stop().unwrap();
+    assert!(lines_parsed >= ticks);
+}
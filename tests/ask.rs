@@ -0,0 +1,115 @@
+use movie::actor;
+
+actor! {
+    CalculatorActor
+        input:
+            Add(u32, u32) -> u32,
+            Divide { a: u32, b: u32 } -> Option<u32>,
+            Ignore -> (),
+            Sleep(u64),
+        on_message:
+            Add(a, b, reply) => reply.send(a + b),
+            Divide { a, b, reply_to } => reply_to.send(a.checked_div(b)),
+            Ignore(_reply) => (),
+            Sleep(ms) => std::thread::sleep(std::time::Duration::from_millis(ms)),
+}
+
+#[test]
+fn test_ask() {
+    use CalculatorActor::{Actor, Input};
+    let actor = Actor {}.start();
+
+    assert_eq!(actor.ask(|reply| Input::Add(2, 3, reply)), Ok(5));
+    let divide = |a, b| move |reply_to| Input::Divide { a, b, reply_to };
+    assert_eq!(actor.ask(divide(6, 3)), Ok(Some(2)));
+    assert_eq!(actor.ask(divide(6, 0)), Ok(None));
+
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_ask_without_reply() {
+    use movie::AskError;
+    use std::time::Duration;
+    use CalculatorActor::{Actor, Input};
+    let actor = Actor {}.start();
+
+    assert_eq!(actor.ask(Input::Ignore), Err(AskError::Closed));
+
+    actor.send(Input::Sleep(200)).unwrap();
+    let reply = actor.ask_timeout(|reply| Input::Add(1, 1, reply), Duration::from_millis(10));
+    assert_eq!(reply, Err(AskError::Timeout));
+
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_ask_deferred() {
+    use std::time::Duration;
+    use CalculatorActor::{Actor, Input};
+    let actor = Actor {}.start();
+
+    actor.send(Input::Sleep(50)).unwrap();
+    let pending = actor.ask_deferred(|reply| Input::Add(1, 2, reply));
+    assert_eq!(pending.try_wait(), Ok(None));
+    assert_eq!(pending.wait_timeout(Duration::from_secs(5)), Ok(3));
+
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_handle_methods() {
+    use movie::AskError;
+    use CalculatorActor::{Actor, HandleExt};
+    let actor = Actor {}.start();
+
+    assert_eq!(actor.add(2, 3), Ok(5));
+    assert_eq!(actor.divide(6, 3), Ok(Some(2)));
+    assert_eq!(actor.ignore(), Err(AskError::Closed));
+    actor.sleep(1).unwrap();
+    // Also available on the address
+    assert_eq!(actor.addr().add(1, 1), Ok(2));
+
+    actor.stop().unwrap();
+}
+
+actor! {
+    RequestActor
+        input:
+            HTTPRequest(String) -> String,
+            GetUTF8Len(String) -> usize,
+            Move(i32) -> i32,
+            Type -> &'static str,
+            Match(u32) -> bool,
+            Loop,
+            Ref -> (),
+            Box(u8) -> u8,
+        on_message:
+            HTTPRequest(path, reply) => reply.send(format!("GET {}", path)),
+            GetUTF8Len(text, reply) => reply.send(text.len()),
+            Move(by, reply) => reply.send(by + 1),
+            Type(reply) => reply.send("request"),
+            Match(n, reply) => reply.send(n % 2 == 0),
+            Loop => (),
+            Ref(reply) => reply.send(()),
+            Box(n, reply) => reply.send(n),
+}
+
+#[test]
+fn test_handle_method_names() {
+    use RequestActor::{Actor, HandleExt};
+    let actor = Actor {}.start();
+
+    // Runs of capitals are one word
+    assert_eq!(actor.http_request("/".to_string()), Ok("GET /".to_string()));
+    assert_eq!(actor.get_utf8_len("é".to_string()), Ok(2));
+    // Keywords are raw identifiers
+    assert_eq!(actor.r#move(1), Ok(2));
+    assert_eq!(actor.r#type(), Ok("request"));
+    assert_eq!(actor.r#match(4), Ok(true));
+    actor.r#loop().unwrap();
+    assert_eq!(actor.r#ref(), Ok(()));
+    assert_eq!(actor.r#box(7), Ok(7));
+
+    actor.stop().unwrap();
+}
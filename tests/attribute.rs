@@ -0,0 +1,141 @@
+use movie::attr::actor;
+use movie::Context;
+
+/// Actor that parses video from V4L2 device
+#[actor(tick_interval = 5)]
+pub struct StreamParsingActor {
+    pub device: String,
+    pub lines_parsed: u64,
+}
+
+#[actor(input_derive(Debug))]
+impl StreamParsingActor {
+    fn on_init(&mut self) {
+        if self.device == "admin secret device" {
+            panic!("No access right for admin secret device");
+        }
+    }
+
+    /// Becomes `StreamParsingActorInput::ChangeSource(String)`
+    pub fn change_source(&mut self, name: String) {
+        self.device = name;
+    }
+
+    /// Reply-bearing: `StreamParsingActorInput::GetState(movie::ReplyTo<(String, u64)>)`
+    pub fn get_state(&self) -> (String, u64) {
+        (self.device.clone(), self.lines_parsed)
+    }
+
+    pub fn add_lines(
+        &mut self,
+        ctx: &mut Context<StreamParsingActorInput>,
+        lines: u64,
+        stop: bool,
+    ) {
+        self.lines_parsed += lines;
+        if stop {
+            ctx.stop();
+        }
+    }
+
+    fn on_tick(&mut self, ctx: &mut Context<StreamParsingActorInput>) {
+        assert!(ctx.tick_count() > 0);
+    }
+
+    fn on_stop(self) -> Self {
+        self
+    }
+}
+
+// Helper methods go in a separate impl block
+impl StreamParsingActor {
+    pub fn new(device: &str) -> Self {
+        StreamParsingActor {
+            device: device.to_string(),
+            lines_parsed: 0,
+        }
+    }
+}
+
+#[actor]
+struct MinimalActor {}
+
+#[actor]
+impl MinimalActor {
+    fn ping(&mut self) {}
+}
+
+#[test]
+fn test_attribute_actor() {
+    let actor: StreamParsingActorHandle = StreamParsingActor::new("video0").start();
+    actor
+        .send(dbg!(StreamParsingActorInput::ChangeSource(
+            "video1".to_string()
+        )))
+        .unwrap();
+    actor
+        .send(StreamParsingActorInput::AddLines(3, false))
+        .unwrap();
+    let (device, lines) = actor.ask(StreamParsingActorInput::GetState).unwrap();
+    assert_eq!(device, "video1");
+    assert_eq!(lines, 3);
+
+    let data = actor.stop().unwrap();
+    assert_eq!(data.lines_parsed, 3);
+}
+
+#[test]
+fn test_attribute_actor_ctx() {
+    let actor = StreamParsingActor::new("video0").start();
+    actor
+        .send(StreamParsingActorInput::AddLines(1, true))
+        .unwrap();
+    let data = actor.join().unwrap();
+    assert_eq!(data.lines_parsed, 1);
+}
+
+#[test]
+fn test_attribute_actor_handle_methods() {
+    let actor = StreamParsingActor::new("video0").start();
+    actor.change_source("video1".to_string()).unwrap();
+    actor.add_lines(2, false).unwrap();
+    assert_eq!(actor.get_state().unwrap(), ("video1".to_string(), 2));
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_minimal_attribute_actor() {
+    let actor = MinimalActor {}.start();
+    actor.send(MinimalActorInput::Ping).unwrap();
+    actor.stop().unwrap();
+}
+
+mod counter {
+    use movie::attr::actor;
+
+    #[actor]
+    pub struct CounterActor {
+        pub count: u32,
+    }
+
+    // Private handlers, the input is still as visible as the struct
+    #[actor]
+    impl CounterActor {
+        fn increment(&mut self) {
+            self.count += 1;
+        }
+        fn get(&self) -> u32 {
+            self.count
+        }
+    }
+}
+
+#[test]
+fn test_input_visibility() {
+    use counter::{CounterActor, CounterActorHandleExt, CounterActorInput};
+
+    let actor = CounterActor { count: 0 }.start();
+    actor.send(CounterActorInput::Increment).unwrap();
+    assert_eq!(actor.get().unwrap(), 1);
+    actor.stop().unwrap();
+}
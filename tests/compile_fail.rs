@@ -0,0 +1,8 @@
+#[test]
+fn test_compile_errors() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+    // Also makes trybuild build the failing cases instead of only checking them, which
+    // reports the assertions of `Pump`, `TestActor` and `ThreadPool` on the actor
+    tests.pass("tests/pass/*.rs");
+}
@@ -0,0 +1,55 @@
+use movie::actor;
+
+actor! {
+    CountdownActor
+        input:
+            Countdown(u32),
+            GetName -> String,
+        data:
+            pub done_tx: std::sync::mpsc::Sender<u32>,
+        on_message:
+            // Counts down by sending messages to itself
+            Countdown(0) => self.done_tx.send(0).unwrap(),
+            Countdown(n) => ctx.addr().send(Input::Countdown(n - 1)).unwrap(),
+            GetName(reply) => reply.send(ctx.name().to_string()),
+}
+
+#[test]
+fn test_self_address() {
+    use std::sync::mpsc::channel;
+    use CountdownActor::{Actor, Input};
+
+    let (done_tx, done_rx) = channel();
+    let actor = Actor { done_tx }.start();
+    actor.send(Input::Countdown(10)).unwrap();
+    assert_eq!(done_rx.recv(), Ok(0));
+    assert_eq!(actor.ask(Input::GetName).unwrap(), "CountdownActor");
+    actor.stop().unwrap();
+}
+
+actor! {
+    SelfStoppingActor
+        data:
+            pub ticks_tx: std::sync::mpsc::Sender<u64>,
+        tick_interval: 1,
+        on_tick:
+            if ctx.tick_count() == 3 {
+                ctx.stop();
+            }
+        on_stop:
+            self.ticks_tx.send(ctx.tick_count()).unwrap();
+}
+
+#[test]
+fn test_stop_from_inside() {
+    use std::sync::mpsc::channel;
+    use SelfStoppingActor::Actor;
+
+    let (ticks_tx, ticks_rx) = channel();
+    let actor = Actor { ticks_tx }.start();
+    assert_eq!(ticks_rx.recv(), Ok(3));
+
+    let addr = actor.addr();
+    actor.stop().unwrap();
+    assert!(!addr.is_alive());
+}
@@ -0,0 +1,116 @@
+use movie::actor;
+
+pub trait Sink {
+    fn write_line(&mut self, line: String);
+}
+
+impl Sink for Vec<String> {
+    fn write_line(&mut self, line: String) {
+        self.push(line);
+    }
+}
+
+#[derive(Default)]
+pub struct LineCount(usize);
+
+impl Sink for LineCount {
+    fn write_line(&mut self, _line: String) {
+        self.0 += 1;
+    }
+}
+
+actor! {
+    StreamParsingActor<S: Sink = Vec<String>> where S: Send + 'static
+        input:
+            Line(String),
+            // `Input` does not have to use `S`
+            Flush,
+        data:
+            pub sink: S,
+            pub buffer: Vec<String>,
+        on_message:
+            Line(line) => self.buffer.push(line),
+            Flush => {
+                for line in self.buffer.drain(..) {
+                    self.sink.write_line(line);
+                }
+            }
+        returns: S,
+        on_stop: self.sink
+}
+
+actor! {
+    ValueActor<T: Clone + Send + 'static>
+        input: Set(T), Get -> Option<T>,
+        data:
+            pub value: Option<T>,
+        on_message:
+            Set(value) => self.value = Some(value),
+            Get(reply) => reply.send(self.value.clone()),
+}
+
+actor! {
+    BorrowingActor<'a>
+        input: Add(u32),
+        data:
+            pub total: &'a mut u32,
+        on_message:
+            Add(n) => *self.total += n,
+}
+
+#[test]
+fn test_generic_actor_with_different_sinks() {
+    use StreamParsingActor::{Actor, Handle, Input};
+
+    // Default parameter
+    let actor: Handle = Actor {
+        sink: vec![],
+        buffer: vec![],
+    }
+    .start();
+    actor.send(Input::Line("a".to_string())).unwrap();
+    actor.send(Input::Flush).unwrap();
+    assert_eq!(actor.stop().unwrap(), vec!["a".to_string()]);
+
+    let actor: Handle<LineCount> = Actor {
+        sink: LineCount::default(),
+        buffer: vec![],
+    }
+    .start();
+    actor.send(Input::Line("a".to_string())).unwrap();
+    actor.send(Input::Line("b".to_string())).unwrap();
+    actor.send(Input::Flush).unwrap();
+    assert_eq!(actor.stop().unwrap().0, 2);
+}
+
+#[test]
+fn test_generic_input() {
+    use ValueActor::{Actor, Input};
+
+    let actor = Actor { value: None }.start();
+    actor.send(Input::Set("a")).unwrap();
+    assert_eq!(actor.ask(Input::Get).unwrap(), Some("a"));
+    actor.stop().unwrap();
+
+    let actor = Actor { value: Some(1) }.start();
+    assert_eq!(actor.ask(Input::Get).unwrap(), Some(1));
+    // Generated methods are generic too
+    use ValueActor::HandleExt;
+    actor.set(2).unwrap();
+    assert_eq!(actor.get().unwrap(), Some(2));
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_actor_borrowing_from_scope() {
+    use BorrowingActor::{Actor, Input};
+
+    let mut total = 0;
+    std::thread::scope(|scope| {
+        let actor = movie::start_with(Actor { total: &mut total }, |run| scope.spawn(run));
+        actor.send(Input::Add(1)).unwrap();
+        actor.send(Input::Add(2)).unwrap();
+        actor.stop().unwrap();
+    });
+    assert_eq!(total, 3);
+}
@@ -0,0 +1,57 @@
+use movie::actor;
+
+use std::sync::mpsc::Sender;
+actor! {
+    SlowTickingActor
+        input: Ping,
+        data:
+            pub pong_tx: Sender<()>,
+            pub ticks_tx: Sender<()>,
+        on_message:
+            Ping => self.pong_tx.send(()).unwrap(),
+        tick_interval: 60000,
+        on_tick:
+            self.ticks_tx.send(()).unwrap();
+}
+
+#[test]
+fn test_messages_do_not_wait_for_tick() {
+    use std::sync::mpsc::channel;
+    use std::time::{Duration, Instant};
+    use SlowTickingActor::{Actor, Input};
+
+    let (pong_tx, pong_rx) = channel();
+    let (ticks_tx, ticks_rx) = channel();
+    let actor = Actor { pong_tx, ticks_tx }.start();
+
+    let started = Instant::now();
+    actor.send(Input::Ping).unwrap();
+    pong_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    actor.stop().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    // The actor stopped long before its first tick
+    assert!(ticks_rx.try_recv().is_err());
+}
+
+actor! {
+    FastTickingActor
+        data:
+            pub ticks_tx: Sender<()>,
+        tick_interval: 1,
+        on_tick:
+            let _ = self.ticks_tx.send(());
+}
+
+#[test]
+fn test_ticks_run_without_messages() {
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+    use FastTickingActor::Actor;
+
+    let (ticks_tx, ticks_rx) = channel();
+    let actor = Actor { ticks_tx }.start();
+    for _ in 0..3 {
+        ticks_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+    actor.stop().unwrap();
+}
@@ -0,0 +1,106 @@
+use movie::actor;
+
+use std::sync::mpsc::{channel, Sender};
+use std::thread::{self, ThreadId};
+
+actor! {
+    MainThreadActor
+        input:
+            Record(String),
+            Quit,
+            Thread -> ThreadId,
+        data:
+            pub records: Vec<String>,
+            pub addr_tx: Option<Sender<movie::Addr<Input>>>,
+        on_init:
+            for addr_tx in self.addr_tx.take().iter() {
+                addr_tx.send(ctx.addr().clone()).unwrap();
+            }
+        on_message:
+            Record(record) => self.records.push(record),
+            Quit => ctx.stop(),
+            Thread(reply) => reply.send(thread::current().id()),
+        returns: Vec<String>,
+        on_stop: self.records,
+}
+
+#[test]
+fn test_run_here() {
+    use MainThreadActor::{Actor, HandleExt, Input};
+
+    let (addr_tx, addr_rx) = channel::<movie::Addr<Input>>();
+    let main = thread::current().id();
+    let sender = thread::spawn(move || {
+        let actor = addr_rx.recv().unwrap();
+        assert_eq!(actor.thread().unwrap(), main);
+        actor.record("from another thread".to_string()).unwrap();
+        actor.quit().unwrap();
+    });
+    let records = Actor {
+        records: vec![],
+        addr_tx: Some(addr_tx),
+    }
+    .run_here();
+    sender.join().unwrap();
+    assert_eq!(records, ["from another thread"]);
+}
+
+#[test]
+fn test_run_with_handle() {
+    use movie::local::LocalJoinHandle;
+    use MainThreadActor::{Actor, HandleExt, Input};
+
+    let (handle_tx, handle_rx) = channel::<movie::Handle<LocalJoinHandle<Vec<String>>, Input>>();
+    let main = thread::current().id();
+    let owner = thread::spawn(move || {
+        let actor = handle_rx.recv().unwrap();
+        assert_eq!(actor.thread().unwrap(), main);
+        actor.record("from the owner".to_string()).unwrap();
+        actor.stop().unwrap()
+    });
+    Actor {
+        records: vec![],
+        addr_tx: None,
+    }
+    .run_with_handle(|handle| handle_tx.send(handle).unwrap());
+    assert_eq!(owner.join().unwrap(), ["from the owner"]);
+}
+
+#[test]
+fn test_pump() {
+    use movie::{Pump, Pumped};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use MainThreadActor::{Actor, HandleExt};
+
+    let mut actor = Pump::new(Actor {
+        records: vec![],
+        addr_tx: None,
+    });
+    let wakeups = Arc::new(AtomicUsize::new(0));
+    let counter = wakeups.clone();
+    actor.set_waker(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    // Nothing to do, `on_init` has nothing to send
+    assert!(matches!(actor.pump(), Pumped::Idle(_)));
+
+    let addr = actor.addr();
+    thread::spawn(move || {
+        for n in 0..20 {
+            addr.record(n.to_string()).unwrap();
+        }
+    })
+    .join()
+    .unwrap();
+    assert_eq!(wakeups.load(Ordering::SeqCst), 20);
+    // Handled in two steps, as there are more messages than `PUMP_BUDGET`
+    assert_eq!(actor.pump(), Pumped::Busy);
+    assert!(matches!(actor.pump(), Pumped::Idle(_)));
+
+    actor.request_stop();
+    match actor.pump() {
+        Pumped::Stopped(records) => assert_eq!(records.len(), 20),
+        _ => panic!("actor did not stop"),
+    }
+}
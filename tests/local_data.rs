@@ -0,0 +1,156 @@
+use movie::actor;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread::{self, ThreadId};
+
+actor! {
+    HistoryActor
+        input:
+            Record(String),
+            Undo,
+            History -> Vec<String>,
+            Thread -> ThreadId,
+        data:
+            pub limit: usize,
+        local_data:
+            // `Rc` can't be sent between threads
+            history: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(vec![])),
+            thread: ThreadId = thread::current().id(),
+            limit: usize = self.limit,
+        on_init:
+            let greeting = "hello".to_string();
+            local.history.borrow_mut().push(greeting);
+        on_message:
+            Record(record) => {
+                if local.history.borrow().len() == local.limit {
+                    return;
+                }
+                local.history.borrow_mut().push(record);
+            },
+            Undo => {
+                local.history.borrow_mut().pop();
+            },
+            History(reply) => reply.send(local.history.borrow().clone()),
+            Thread(reply) => reply.send(local.thread),
+        returns: usize,
+        on_stop: local.history.borrow().len(),
+}
+
+#[test]
+fn test_local_data() {
+    use HistoryActor::{Actor, HandleExt};
+
+    let actor = Actor { limit: 3 }.start();
+    assert_ne!(actor.thread().unwrap(), thread::current().id());
+
+    for record in ["a", "b", "c"] {
+        actor.record(record.to_string()).unwrap();
+    }
+    // `return` leaves the handler, not the actor
+    assert_eq!(actor.history().unwrap(), ["hello", "a", "b"]);
+    actor.undo().unwrap();
+    assert_eq!(actor.history().unwrap(), ["hello", "a"]);
+    assert_eq!(actor.stop().unwrap(), 2);
+}
+
+#[test]
+fn test_local_data_pump() {
+    use movie::{Pump, Pumped};
+    use HistoryActor::{Actor, HandleExt};
+
+    let mut actor = Pump::new(Actor { limit: 3 });
+    assert!(matches!(actor.pump(), Pumped::Idle(_)));
+
+    let addr = actor.addr();
+    addr.record("a".to_string()).unwrap();
+    addr.undo().unwrap();
+    addr.record("b".to_string()).unwrap();
+    assert!(matches!(actor.pump(), Pumped::Idle(_)));
+
+    let owner = thread::spawn(move || (addr.thread().unwrap(), addr.history().unwrap()));
+    // Answered by the pumping thread
+    while !owner.is_finished() {
+        actor.pump();
+    }
+    let (thread, history) = owner.join().unwrap();
+    assert_eq!(thread, thread::current().id());
+    assert_eq!(history, ["hello", "b"]);
+
+    actor.request_stop();
+    match actor.pump() {
+        Pumped::Stopped(records) => assert_eq!(records, 2),
+        _ => panic!("actor did not stop"),
+    }
+}
+
+actor! {
+    SamplerActor
+        input:
+            Samples -> Vec<u64>,
+        local_data:
+            samples: Rc<RefCell<Vec<u64>>> = Rc::default(),
+            ticks: u64 = 0,
+        tick_interval: 5,
+        timers:
+            sample(12) => local.samples.borrow_mut().push(local.ticks),
+        on_message:
+            Samples(reply) => reply.send(local.samples.borrow().clone()),
+        on_tick:
+            local.ticks += 1;
+        returns: u64,
+        on_stop: local.ticks,
+}
+
+#[test]
+fn test_local_data_test_actor() {
+    use std::time::Duration;
+
+    let mut actor = movie::TestActor::new(SamplerActor::Actor {});
+    actor.step_ticks(10);
+    // Fired at 12ms, 24ms, 36ms and 48ms, with a tick every 5ms
+    assert_eq!(actor.ask(SamplerActor::Input::Samples), [2, 4, 7, 9]);
+
+    actor.advance(Duration::from_millis(50));
+    assert_eq!(actor.ask(SamplerActor::Input::Samples).len(), 8);
+    assert_eq!(actor.stop(), 20);
+}
+
+actor! {
+    TokenActor
+        data:
+            pub token: std::sync::Arc<()>,
+        local_data:
+            token: std::sync::Arc<()> = self.token.clone(),
+        returns: usize,
+        on_stop: std::sync::Arc::strong_count(&local.token),
+}
+
+#[test]
+fn test_local_data_dropped_without_stop() {
+    use movie::Pump;
+    use std::sync::Arc;
+
+    let token = Arc::new(());
+    let mut actor = Pump::new(TokenActor::Actor {
+        token: token.clone(),
+    });
+    actor.pump();
+    // The actor's data and local data
+    assert_eq!(Arc::strong_count(&token), 3);
+    drop(actor);
+    assert_eq!(Arc::strong_count(&token), 1);
+
+    let actor = movie::TestActor::new(TokenActor::Actor {
+        token: token.clone(),
+    });
+    assert_eq!(Arc::strong_count(&token), 3);
+    drop(actor);
+    assert_eq!(Arc::strong_count(&token), 1);
+
+    // Stopped, `on_stop` gets the local data
+    let actor = movie::TestActor::new(TokenActor::Actor {
+        token: token.clone(),
+    });
+    assert_eq!(actor.stop(), 3);
+}
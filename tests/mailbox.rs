@@ -0,0 +1,142 @@
+use movie::actor;
+
+// Actors that record pushed numbers, with different overflow policies. `Wait` blocks the
+// actor until the sender is dropped, so that messages pile up in the mailbox.
+macro_rules! recording_actor {
+    ($name:ident, $overflow:ident) => {
+        actor! {
+            $name
+                input:
+                    Wait(std::sync::mpsc::Receiver<()>),
+                    Push(u32),
+                    Get -> Vec<u32>,
+                data:
+                    pub pushed: Vec<u32>,
+                mailbox_capacity: 2
+                mailbox_overflow: $overflow
+                on_message:
+                    Wait(rx) => { let _ = rx.recv(); },
+                    Push(n) => self.pushed.push(n),
+                    Get(reply) => reply.send(self.pushed.clone()),
+        }
+    };
+}
+
+recording_actor!(BlockingActor, Block);
+recording_actor!(FailingActor, Fail);
+recording_actor!(DropNewestActor, DropNewest);
+recording_actor!(DropOldestActor, DropOldest);
+
+actor! {
+    UnboundedActor
+        input: Wait(std::sync::mpsc::Receiver<()>), Ping,
+        on_message:
+            Wait(rx) => { let _ = rx.recv(); },
+            Ping => (),
+}
+
+use movie::{Addr, SendError};
+use std::sync::mpsc::{channel, Sender};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Blocks the actor and fills its mailbox with 1 and 2. Drop the returned sender to
+/// unblock it.
+fn fill<TX>(
+    addr: &Addr<TX>,
+    wait: fn(std::sync::mpsc::Receiver<()>) -> TX,
+    push: fn(u32) -> TX,
+) -> Sender<()> {
+    let (tx, rx) = channel();
+    addr.send(wait(rx)).unwrap();
+    drain(addr);
+    addr.send(push(1)).unwrap();
+    addr.send(push(2)).unwrap();
+    assert_eq!(addr.queue_len(), 2);
+    tx
+}
+
+/// Waits until the actor handles all waiting messages.
+fn drain<TX>(addr: &Addr<TX>) {
+    while addr.queue_len() > 0 {
+        sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_fail() {
+    use FailingActor::{Actor, Input};
+    let actor = Actor { pushed: vec![] }.start();
+    assert_eq!(actor.capacity(), Some(2));
+    let gate = fill(&actor, Input::Wait, Input::Push);
+    match actor.send(Input::Push(3)) {
+        Err(SendError::Full(Input::Push(3))) => (),
+        _ => panic!("expected SendError::Full"),
+    }
+    drop(gate);
+    drain(&actor);
+    assert_eq!(actor.ask(Input::Get).unwrap(), vec![1, 2]);
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_drop_newest() {
+    use DropNewestActor::{Actor, Input};
+    let actor = Actor { pushed: vec![] }.start();
+    let gate = fill(&actor, Input::Wait, Input::Push);
+    actor.send(Input::Push(3)).unwrap();
+    assert_eq!(actor.queue_len(), 2);
+    drop(gate);
+    drain(&actor);
+    assert_eq!(actor.ask(Input::Get).unwrap(), vec![1, 2]);
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_drop_oldest() {
+    use DropOldestActor::{Actor, Input};
+    let actor = Actor { pushed: vec![] }.start();
+    let gate = fill(&actor, Input::Wait, Input::Push);
+    actor.send(Input::Push(3)).unwrap();
+    assert_eq!(actor.queue_len(), 2);
+    drop(gate);
+    drain(&actor);
+    assert_eq!(actor.ask(Input::Get).unwrap(), vec![2, 3]);
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_block() {
+    use BlockingActor::{Actor, Input};
+    let actor = Actor { pushed: vec![] }.start();
+    let gate = fill(&actor, Input::Wait, Input::Push);
+    match actor.try_send(Input::Push(3)) {
+        Err(SendError::Full(Input::Push(3))) => (),
+        _ => panic!("expected SendError::Full"),
+    }
+
+    let addr = actor.addr();
+    let sender = std::thread::spawn(move || addr.send(Input::Push(3)).is_ok());
+    sleep(Duration::from_millis(50));
+    assert!(!sender.is_finished());
+    drop(gate);
+    assert!(sender.join().unwrap());
+    drain(&actor);
+    assert_eq!(actor.ask(Input::Get).unwrap(), vec![1, 2, 3]);
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_unbounded() {
+    use UnboundedActor::{Actor, Input};
+    let actor = Actor {}.start();
+    assert_eq!(actor.capacity(), None);
+    let (gate, rx) = channel();
+    actor.send(Input::Wait(rx)).unwrap();
+    for _ in 0..1000 {
+        actor.send(Input::Ping).unwrap();
+    }
+    assert!(actor.queue_len() >= 999);
+    drop(gate);
+    actor.stop().unwrap();
+}
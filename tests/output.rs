@@ -0,0 +1,86 @@
+use movie::actor;
+
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+actor! {
+    DownloadActor
+        input:
+            Download(String),
+            // Replies once the previous messages are handled
+            Sync -> (),
+        output:
+            Started(String),
+            Finished { url: String, bytes: usize },
+            Stopped,
+        output_derive: Debug, PartialEq,
+        on_message:
+            Download(url) => {
+                ctx.emit(Output::Started(url.clone()));
+                let bytes = url.len();
+                ctx.emit(Output::Finished { url, bytes });
+            },
+            Sync(reply) => reply.send(()),
+        on_stop:
+            ctx.emit(Output::Stopped),
+}
+
+#[test]
+fn test_subscribers_get_events() {
+    use DownloadActor::{Actor, HandleExt, Output};
+    let actor = Actor {}.start();
+
+    let first: Receiver<Output> = actor.subscribe();
+    let second = actor.addr().subscribe();
+    actor.download("a.io".to_string()).unwrap();
+
+    for events in &[first, second] {
+        assert_eq!(events.recv(), Ok(Output::Started("a.io".to_string())));
+        assert_eq!(
+            events.recv(),
+            Ok(Output::Finished {
+                url: "a.io".to_string(),
+                bytes: 4
+            })
+        );
+    }
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_closed_subscribers_are_dropped() {
+    use DownloadActor::{Actor, HandleExt, Output};
+    let actor = Actor {}.start();
+
+    let kept: Receiver<Output> = actor.subscribe();
+    let dropped: Receiver<Output> = actor.subscribe();
+    drop(dropped);
+    actor.download("a.io".to_string()).unwrap();
+    actor.download("b.io".to_string()).unwrap();
+    actor.sync().unwrap();
+    assert_eq!(kept.try_iter().count(), 4);
+
+    // Events emitted before subscribing are not received
+    let late: Receiver<Output> = actor.subscribe();
+    assert!(late.try_recv().is_err());
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_subscription_ends_when_actor_stops() {
+    use DownloadActor::{Actor, Output};
+    let actor = Actor {}.start();
+
+    let events: Receiver<Output> = actor.subscribe();
+    let addr = actor.addr();
+    actor.stop().unwrap();
+
+    // Events emitted in `on_stop` are still received
+    assert_eq!(
+        events.recv_timeout(Duration::from_secs(5)),
+        Ok(Output::Stopped)
+    );
+    assert!(events.recv_timeout(Duration::from_secs(5)).is_err());
+    // Subscribing to a stopped actor gives a disconnected receiver
+    assert!(addr.subscribe().recv().is_err());
+}
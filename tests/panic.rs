@@ -0,0 +1,42 @@
+use movie::actor;
+
+actor! {
+    FragileActor
+        input:
+            Ping,
+            Panic,
+        on_message:
+            Ping => (),
+            Panic => panic!("fragile actor broke"),
+}
+
+#[test]
+fn test_send_to_panicked_actor() {
+    use std::thread::sleep;
+    use std::time::Duration;
+    use FragileActor::{Actor, Input};
+
+    let actor = Actor {}.start();
+    actor.send(Input::Ping).unwrap();
+    actor.send(Input::Panic).unwrap();
+
+    // Wait for the actor to die, at which point the message is given back
+    let mut result = actor.send(Input::Ping);
+    for _ in 0..500 {
+        if result.is_err() {
+            break;
+        }
+        sleep(Duration::from_millis(10));
+        result = actor.send(Input::Ping);
+    }
+    match result {
+        Err(err) => match err.into_inner() {
+            Input::Ping => (),
+            Input::Panic => panic!("wrong message given back"),
+        },
+        Ok(()) => panic!("actor did not stop"),
+    }
+
+    let payload = actor.stop().unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"fragile actor broke"));
+}
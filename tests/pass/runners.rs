@@ -0,0 +1,27 @@
+use movie::actor;
+
+actor! {
+    GuiActor
+        local_data:
+            labels: std::rc::Rc<Vec<String>> = Default::default(),
+}
+
+actor! {
+    CounterActor
+        on_init:
+            let mut count = 0;
+        on_tick:
+            count += 1;
+        returns: u64,
+        on_stop: count,
+}
+
+fn main() {
+    // Actors with `local_data` run on any single thread
+    GuiActor::Actor {}.start().stop().unwrap();
+    movie::Pump::new(GuiActor::Actor {}).request_stop();
+    movie::TestActor::new(GuiActor::Actor {}).stop();
+
+    // Variables declared in `on_init` need `Actor::run()`
+    CounterActor::Actor {}.start().stop().unwrap();
+}
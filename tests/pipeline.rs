@@ -0,0 +1,199 @@
+use movie::actor;
+
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+
+actor! {
+    CaptureActor
+        input: Capture(u32),
+        output: Frame(u32),
+        data:
+            pub started_tx: Sender<&'static str>,
+        on_init:
+            self.started_tx.send("capture").unwrap();
+            // Emitted before anyone could subscribe with `Handle::subscribe()`
+            ctx.emit(Output::Frame(0));
+        on_message:
+            Capture(n) => ctx.emit(Output::Frame(n)),
+}
+
+actor! {
+    ParseActor
+        input: Parse(u32),
+        output: Line(String),
+        data:
+            pub started_tx: Sender<&'static str>,
+        on_init:
+            self.started_tx.send("parse").unwrap();
+        on_message:
+            Parse(n) => {
+                // Slow, so that frames are still waiting when capture stops
+                std::thread::sleep(Duration::from_millis(1));
+                ctx.emit(Output::Line(format!("frame {}", n)));
+            },
+}
+
+actor! {
+    PublishActor
+        input: Publish(String),
+        data:
+            pub started_tx: Sender<&'static str>,
+            pub published: Vec<String>,
+        on_init:
+            self.started_tx.send("publish").unwrap();
+        on_message:
+            Publish(line) => self.published.push(line),
+        on_stop:
+            self.started_tx.send("publish stopped").unwrap();
+            assert_eq!(self.published.len(), 11);
+            assert_eq!(self.published[10], "frame 10");
+}
+
+#[test]
+fn test_pipeline() {
+    use movie::Pipeline;
+
+    let (started_tx, started_rx) = channel();
+    let mut pipeline = Pipeline::new();
+    let capture = pipeline.add(
+        "capture",
+        CaptureActor::Actor {
+            started_tx: started_tx.clone(),
+        },
+    );
+    let parse = pipeline.add(
+        "parse",
+        ParseActor::Actor {
+            started_tx: started_tx.clone(),
+        },
+    );
+    let publish = pipeline.add(
+        "publish",
+        PublishActor::Actor {
+            started_tx,
+            published: vec![],
+        },
+    );
+    pipeline.connect(&capture, &parse, |frame: CaptureActor::Output| {
+        let CaptureActor::Output::Frame(n) = frame;
+        Some(ParseActor::Input::Parse(n))
+    });
+    pipeline.connect(&parse, &publish, |line: ParseActor::Output| {
+        let ParseActor::Output::Line(line) = line;
+        Some(PublishActor::Input::Publish(line))
+    });
+    // Messages can be sent before starting
+    capture.send(CaptureActor::Input::Capture(1)).unwrap();
+
+    let system = pipeline.start().unwrap();
+    let started: Vec<_> = started_rx.iter().take(3).collect();
+    assert_eq!(started, ["publish", "parse", "capture"]);
+
+    for n in 2..=10 {
+        capture.send(CaptureActor::Input::Capture(n)).unwrap();
+    }
+    let report = system.shutdown(Duration::from_secs(5));
+    assert_eq!(report.stopped, ["capture", "parse", "publish"]);
+    assert!(report.is_clean(), "{:?}", report);
+    // `PublishActor` got all the frames
+    assert_eq!(started_rx.recv(), Ok("publish stopped"));
+}
+
+#[test]
+fn test_pipeline_filtering() {
+    use movie::Pipeline;
+
+    let (started_tx, started_rx) = channel();
+    let mut pipeline = Pipeline::new();
+    let capture = pipeline.add(
+        "capture",
+        CaptureActor::Actor {
+            started_tx: started_tx.clone(),
+        },
+    );
+    let publish = pipeline.add(
+        "publish",
+        PublishActor::Actor {
+            started_tx,
+            published: (0..10).map(|n| n.to_string()).collect(),
+        },
+    );
+    // Only frame 10 gets through, completing the list
+    pipeline.connect(
+        &capture,
+        &publish,
+        |frame: CaptureActor::Output| match frame {
+            CaptureActor::Output::Frame(10) => {
+                Some(PublishActor::Input::Publish("frame 10".into()))
+            }
+            CaptureActor::Output::Frame(_) => None,
+        },
+    );
+    let system = pipeline.start().unwrap();
+    for n in 1..=10 {
+        capture.send(CaptureActor::Input::Capture(n)).unwrap();
+    }
+    assert!(system.shutdown(Duration::from_secs(5)).is_clean());
+    assert_eq!(started_rx.iter().nth(2), Some("publish stopped"));
+}
+
+#[test]
+fn test_pipeline_cycle() {
+    use movie::{Pipeline, PipelineError};
+
+    let (started_tx, started_rx) = channel();
+    let mut pipeline = Pipeline::new();
+    let first = pipeline.add(
+        "first",
+        ParseActor::Actor {
+            started_tx: started_tx.clone(),
+        },
+    );
+    let second = pipeline.add("second", ParseActor::Actor { started_tx });
+    let to_parse = |line: ParseActor::Output| {
+        let ParseActor::Output::Line(line) = line;
+        Some(ParseActor::Input::Parse(line.len() as u32))
+    };
+    pipeline.connect(&first, &second, to_parse);
+    pipeline.connect(&second, &first, to_parse);
+
+    match pipeline.start().err().unwrap() {
+        PipelineError::Cycle(names) => assert_eq!(names, ["first", "second"]),
+        err => panic!("unexpected error: {}", err),
+    }
+    // Nothing was started
+    assert!(started_rx.recv().is_err());
+}
+
+actor! {
+    HugeStackActor
+        output: Frame(u32),
+        // Larger than the address space, the thread can't be spawned
+        stack_size: 4_611_686_018_427_387_904,
+        on_init:
+            ctx.emit(Output::Frame(0));
+}
+
+#[test]
+fn test_pipeline_spawn_failure() {
+    use movie::{Pipeline, PipelineError};
+
+    let (started_tx, started_rx) = channel();
+    let mut pipeline = Pipeline::new();
+    let huge = pipeline.add("huge", HugeStackActor::Actor {});
+    let parse = pipeline.add("parse", ParseActor::Actor { started_tx });
+    pipeline.connect(&huge, &parse, |frame: HugeStackActor::Output| {
+        let HugeStackActor::Output::Frame(n) = frame;
+        Some(ParseActor::Input::Parse(n))
+    });
+
+    match pipeline.start().err().unwrap() {
+        PipelineError::Spawn(name, _) => assert_eq!(name, "huge"),
+        err => panic!("unexpected error: {}", err),
+    }
+    // `parse` was started first, and is asked to stop
+    assert_eq!(started_rx.recv(), Ok("parse"));
+    while parse.is_alive() {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,125 @@
+use movie::actor;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+actor! {
+    CounterActor
+        input:
+            Add(u32),
+            // Sleeps, to keep a thread busy
+            Work,
+            Threads -> HashSet<std::thread::ThreadId>,
+        data:
+            pub count: u32,
+            pub threads: HashSet<std::thread::ThreadId>,
+        on_message:
+            Add(n) => {
+                self.count += n;
+                self.threads.insert(std::thread::current().id());
+            },
+            Work => std::thread::sleep(Duration::from_millis(1)),
+            Threads(reply) => reply.send(self.threads.clone()),
+        returns: u32,
+        on_stop: self.count,
+}
+
+#[test]
+fn test_many_actors_few_threads() {
+    use movie::ThreadPool;
+    use CounterActor::{Actor, HandleExt};
+
+    let pool = ThreadPool::new(2);
+    assert_eq!(pool.threads(), 2);
+    let actors: Vec<_> = (0..200)
+        .map(|_| {
+            pool.start(Actor {
+                count: 0,
+                threads: HashSet::new(),
+            })
+        })
+        .collect();
+    for (n, actor) in actors.iter().enumerate() {
+        actor.add(n as u32).unwrap();
+        actor.add(1).unwrap();
+    }
+    let mut threads = HashSet::new();
+    for actor in &actors {
+        threads.extend(actor.threads().unwrap());
+    }
+    assert!(threads.len() <= 2, "{:?}", threads);
+    for (n, actor) in actors.into_iter().enumerate() {
+        assert_eq!(actor.stop().unwrap(), n as u32 + 1);
+    }
+}
+
+#[test]
+fn test_fair_scheduling() {
+    use movie::ThreadPool;
+    use CounterActor::{Actor, HandleExt};
+
+    // With one thread, a busy actor can't keep it to itself
+    let pool = ThreadPool::new(1);
+    let new_actor = || {
+        pool.start(Actor {
+            count: 0,
+            threads: HashSet::new(),
+        })
+    };
+    let busy = new_actor();
+    let other = new_actor();
+    for _ in 0..200 {
+        busy.work().unwrap();
+    }
+    other.add(1).unwrap();
+    other.threads().unwrap();
+    assert!(busy.queue_len() > 100, "{}", busy.queue_len());
+
+    assert_eq!(other.stop().unwrap(), 1);
+    assert_eq!(busy.stop().unwrap(), 0);
+}
+
+actor! {
+    TickingActor
+        input:
+            Panic,
+        data:
+            pub stop_after: Option<u64>,
+        tick_interval: 1,
+        timers:
+            check(2) => {
+                if self.stop_after.is_some_and(|ticks| ctx.tick_count() >= ticks) {
+                    ctx.stop();
+                }
+            },
+        on_message:
+            Panic => panic!("panicked in a pool"),
+        returns: u64,
+        on_stop: ctx.tick_count(),
+}
+
+#[test]
+fn test_ticks_timers_and_panics() {
+    use movie::ThreadPool;
+    use TickingActor::{Actor, HandleExt};
+
+    let pool = ThreadPool::new(2);
+    let stopping = pool.start(Actor {
+        stop_after: Some(10),
+    });
+    let panicking = pool.start(Actor { stop_after: None });
+    // Stops by itself
+    assert!(stopping.join().unwrap() >= 10);
+
+    let addr = panicking.addr();
+    panicking.panic().unwrap();
+    let payload = panicking.join().unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"panicked in a pool"));
+    assert!(!addr.is_alive());
+
+    // The pool still works
+    let actor = pool.start(Actor {
+        stop_after: Some(10),
+    });
+    assert!(actor.join().unwrap() >= 10);
+}
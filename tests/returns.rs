@@ -0,0 +1,74 @@
+use movie::actor;
+
+actor! {
+    LineCountingActor
+        input: Line(String),
+        data:
+            pub lines: Vec<String>,
+        on_message:
+            Line(line) => self.lines.push(line),
+        returns: usize,
+        on_stop:
+            self.lines.len()
+}
+
+actor! {
+    FinalDataActor
+        input: Add(u32),
+        data:
+            pub sum: u32,
+        on_message:
+            Add(n) => self.sum += n,
+        // The whole actor struct can be given back
+        returns: Actor,
+        on_stop: self
+}
+
+actor! {
+    SelfStoppingActor
+        tick_interval: 1,
+        on_tick:
+            if ctx.tick_count() == 3 {
+                ctx.stop();
+            }
+        returns: u64,
+        on_stop: ctx.tick_count()
+}
+
+#[test]
+fn test_stop_returns_value() {
+    use LineCountingActor::{Actor, Input};
+    let actor = Actor { lines: vec![] }.start();
+    actor.send(Input::Line("a".to_string())).unwrap();
+    actor.send(Input::Line("b".to_string())).unwrap();
+    assert_eq!(actor.stop().unwrap(), 2);
+}
+
+#[test]
+fn test_stop_returns_data() {
+    use FinalDataActor::{Actor, Input};
+    let actor = Actor { sum: 0 }.start();
+    actor.send(Input::Add(2)).unwrap();
+    actor.send(Input::Add(3)).unwrap();
+    let data = actor.stop().unwrap();
+    assert_eq!(data.sum, 5);
+}
+
+#[test]
+fn test_join_returns_value() {
+    let actor = SelfStoppingActor::Actor {}.start();
+    assert_eq!(actor.join().unwrap(), 3);
+}
+
+#[test]
+fn test_stop_timeout_returns_value() {
+    use movie::StopOutcome;
+    use std::time::Duration;
+    use LineCountingActor::{Actor, Input};
+    let actor = Actor { lines: vec![] }.start();
+    actor.send(Input::Line("a".to_string())).unwrap();
+    match actor.stop_timeout(Duration::from_secs(5)) {
+        StopOutcome::Stopped(lines) => assert_eq!(lines, 1),
+        outcome => panic!("expected Stopped, got {:?}", outcome),
+    }
+}
@@ -0,0 +1,64 @@
+use movie::actor;
+
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+
+actor! {
+    SlowTickActor
+        data:
+            pub schedule: Option<movie::TickSchedule>,
+            pub ticks_tx: Sender<(movie::TickSchedule, u64)>,
+        tick_interval: 20,
+        tick_schedule: Skip,
+        on_init:
+            if let Some(schedule) = self.schedule {
+                ctx.set_tick_schedule(schedule);
+            }
+        on_tick:
+            self.ticks_tx
+                .send((ctx.tick_schedule(), ctx.missed_ticks()))
+                .unwrap();
+            // The first tick takes more than 3 intervals
+            if ctx.tick_count() == 1 {
+                std::thread::sleep(Duration::from_millis(70));
+            }
+            if ctx.tick_count() == 3 {
+                ctx.stop();
+            }
+}
+
+fn missed_ticks(schedule: Option<movie::TickSchedule>) -> Vec<u64> {
+    let (ticks_tx, ticks_rx) = channel();
+    let actor = SlowTickActor::Actor { schedule, ticks_tx }.start();
+    let ticks: Vec<_> = ticks_rx.iter().collect();
+    actor.join().unwrap();
+    let expected = schedule.unwrap_or(movie::TickSchedule::Skip);
+    assert!(ticks.iter().all(|&(schedule, _)| schedule == expected));
+    ticks.into_iter().map(|(_, missed)| missed).collect()
+}
+
+#[test]
+fn test_fixed_delay() {
+    assert_eq!(
+        missed_ticks(Some(movie::TickSchedule::FixedDelay)),
+        [0, 0, 0]
+    );
+}
+
+#[test]
+fn test_catch_up() {
+    let missed = missed_ticks(Some(movie::TickSchedule::CatchUp));
+    assert_eq!(missed[0], 0);
+    // The second tick is late by at least 2 intervals, the third one by less
+    assert!(missed[1] >= 2, "{:?}", missed);
+    assert!(missed[2] < missed[1], "{:?}", missed);
+}
+
+#[test]
+fn test_skip() {
+    let missed = missed_ticks(None);
+    assert_eq!(missed[0], 0);
+    // Ticks missed during the first one are skipped, the third one is on time
+    assert!(missed[1] >= 2, "{:?}", missed);
+    assert_eq!(missed[2], 0);
+}
@@ -0,0 +1,78 @@
+use movie::actor;
+
+use std::sync::mpsc::{channel, Sender};
+use std::time::{Duration, Instant};
+
+actor! {
+    AlarmActor
+        input:
+            Ring(&'static str),
+        data:
+            pub rings_tx: Sender<(&'static str, Instant)>,
+        on_message:
+            Ring(name) => self.rings_tx.send((name, Instant::now())).unwrap(),
+}
+
+#[test]
+fn test_send_after() {
+    use AlarmActor::{Actor, Input};
+
+    let (rings_tx, rings_rx) = channel();
+    let actor = Actor { rings_tx }.start();
+    let start = Instant::now();
+    let cancelled = actor.send_after(Duration::from_millis(10), Input::Ring("cancelled"));
+    actor.send_after(Duration::from_millis(30), Input::Ring("late"));
+    actor.send_at(start + Duration::from_millis(20), Input::Ring("early"));
+    cancelled.cancel();
+    assert!(cancelled.is_cancelled());
+
+    let (name, at) = rings_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(name, "early");
+    assert!(at >= start + Duration::from_millis(20));
+    let (name, at) = rings_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(name, "late");
+    assert!(at >= start + Duration::from_millis(30));
+    actor.stop().unwrap();
+    assert!(rings_rx.try_recv().is_err());
+}
+
+#[test]
+fn test_send_interval() {
+    use AlarmActor::{Actor, Input};
+
+    let (rings_tx, rings_rx) = channel();
+    let actor = Actor { rings_tx }.start();
+    let mut count = 0;
+    let names = ["first", "second", "third"];
+    let interval = actor.send_interval(Duration::from_millis(5), move || {
+        count += 1;
+        Input::Ring(names[(count - 1) % 3])
+    });
+    let rings: Vec<_> = rings_rx.iter().take(4).map(|(name, _)| name).collect();
+    assert_eq!(rings, ["first", "second", "third", "first"]);
+
+    interval.cancel();
+    // A message may have been sent while cancelling
+    std::thread::sleep(Duration::from_millis(20));
+    rings_rx.try_iter().for_each(drop);
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(rings_rx.try_recv().is_err());
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_many_delayed_messages() {
+    use AlarmActor::{Actor, Input};
+
+    let (rings_tx, rings_rx) = channel();
+    let actor = Actor { rings_tx }.start();
+    let start = Instant::now();
+    // Some are due after more than a full turn of the timer wheel
+    for millis in (0..=999).step_by(3) {
+        actor.send_after(Duration::from_millis(millis), Input::Ring("ring"));
+    }
+    let rings: Vec<Instant> = rings_rx.iter().take(334).map(|(_, at)| at).collect();
+    assert!(rings.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(rings[333] >= start + Duration::from_millis(999));
+    actor.stop().unwrap();
+}
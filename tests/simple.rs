@@ -15,6 +15,6 @@ fn test_simple_actor() {
     // Create and spawn the actor
     let actor = Actor {}.start();
 
-    actor.send(Input::Ping);
-    actor.stop(); // Will block, waiting for actor.
+    actor.send(Input::Ping).unwrap();
+    actor.stop().unwrap(); // Will block, waiting for actor.
 }
@@ -0,0 +1,106 @@
+use movie::actor;
+
+actor! {
+    StuckActor
+        input:
+            Hang(std::sync::mpsc::Receiver<()>),
+            Panic,
+        on_message:
+            Hang(rx) => { let _ = rx.recv(); },
+            Panic => panic!("stuck actor broke"),
+}
+
+/// Spawner with its own handle type, to check that stopping does not depend on
+/// `std::thread::JoinHandle`.
+pub struct CustomHandle(std::thread::JoinHandle<()>);
+
+impl movie::JoinableHandle for CustomHandle {
+    type Output = ();
+    fn join(self) -> std::thread::Result<()> {
+        self.0.join()
+    }
+}
+
+pub fn custom_spawn<F: FnOnce() + Send + 'static>(f: F) -> CustomHandle {
+    CustomHandle(std::thread::spawn(f))
+}
+
+actor! {
+    CustomSpawnedActor
+        input: Hang(std::sync::mpsc::Receiver<()>),
+        on_message:
+            Hang(rx) => { let _ = rx.recv(); },
+        spawner: custom_spawn,
+        spawner_return_type: CustomHandle,
+}
+
+use movie::StopOutcome;
+use std::sync::mpsc::channel;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn test_stop_timeout() {
+    use StuckActor::{Actor, Input};
+    let actor = Actor {}.start();
+    let (gate, rx) = channel();
+    actor.send(Input::Hang(rx)).unwrap();
+
+    let actor = match actor.stop_timeout(Duration::from_millis(50)) {
+        StopOutcome::TimedOut(actor) => actor,
+        outcome => panic!("expected TimedOut, got {:?}", outcome),
+    };
+    assert!(!actor.is_finished());
+    drop(gate);
+    match actor.stop_timeout(Duration::from_secs(5)) {
+        StopOutcome::Stopped(()) => (),
+        outcome => panic!("expected Stopped, got {:?}", outcome),
+    }
+}
+
+#[test]
+fn test_stop_timeout_panicked() {
+    use StuckActor::{Actor, Input};
+    let actor = Actor {}.start();
+    actor.send(Input::Panic).unwrap();
+    match actor.stop_timeout(Duration::from_secs(5)) {
+        StopOutcome::Panicked(payload) => {
+            assert_eq!(payload.downcast_ref::<&str>(), Some(&"stuck actor broke"))
+        }
+        outcome => panic!("expected Panicked, got {:?}", outcome),
+    }
+}
+
+#[test]
+fn test_request_stop() {
+    use StuckActor::Actor;
+    let actor = Actor {}.start();
+    assert!(!actor.is_finished());
+    actor.request_stop();
+    for _ in 0..500 {
+        if actor.is_finished() {
+            break;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    assert!(actor.is_finished());
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_custom_spawner() {
+    use CustomSpawnedActor::{Actor, Input};
+    let actor = Actor {}.start();
+    let (gate, rx) = channel();
+    actor.send(Input::Hang(rx)).unwrap();
+
+    let actor = match actor.stop_timeout(Duration::from_millis(50)) {
+        StopOutcome::TimedOut(actor) => actor,
+        outcome => panic!("expected TimedOut, got {:?}", outcome),
+    };
+    drop(gate);
+    match actor.stop_timeout(Duration::from_secs(5)) {
+        StopOutcome::Stopped(()) => (),
+        outcome => panic!("expected Stopped, got {:?}", outcome),
+    }
+}
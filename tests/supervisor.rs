@@ -0,0 +1,214 @@
+use movie::actor;
+
+actor! {
+    CounterActor
+        input:
+            Increment,
+            Get -> u32,
+            Panic,
+        data:
+            pub count: u32,
+        on_message:
+            Increment => self.count += 1,
+            Get(reply) => reply.send(self.count),
+            Panic => panic!("counter actor broke"),
+}
+
+use movie::supervisor::{Backoff, Policy, Strategy, Supervised, Supervisor, SupervisorError};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use CounterActor::{Actor, Input};
+
+fn start_supervised(supervisor: &Supervisor) -> Supervised<Input> {
+    supervisor.supervise(|| Actor { count: 0 }.start())
+}
+
+/// Asks for the count, waiting for the actor to be restarted if needed.
+fn get(actor: &Supervised<Input>) -> u32 {
+    for _ in 0..500 {
+        if let Ok(count) = actor.addr().ask(Input::Get) {
+            return count;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    panic!("actor was not restarted");
+}
+
+/// Makes the actor panic and waits until its address is no longer valid.
+fn crash(actor: &Supervised<Input>) {
+    let addr = actor.addr();
+    addr.send(Input::Panic).unwrap();
+    while addr.is_alive() {
+        sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_one_for_one() {
+    let supervisor = Supervisor::start(Policy::default());
+    let first = start_supervised(&supervisor);
+    let second = start_supervised(&supervisor);
+    first.send(Input::Increment).unwrap();
+    second.send(Input::Increment).unwrap();
+    second.send(Input::Increment).unwrap();
+    assert_eq!(get(&first), 1);
+    assert_eq!(get(&second), 2);
+
+    crash(&first);
+    // Restarted with fresh data, the other actor keeps its state
+    assert_eq!(get(&first), 0);
+    assert_eq!(get(&second), 2);
+
+    supervisor.stop().unwrap();
+    assert!(!first.addr().is_alive());
+    assert!(!second.addr().is_alive());
+}
+
+#[test]
+fn test_one_for_all() {
+    let supervisor = Supervisor::start(Policy {
+        strategy: Strategy::OneForAll,
+        ..Policy::default()
+    });
+    let first = start_supervised(&supervisor);
+    let second = start_supervised(&supervisor);
+    second.send(Input::Increment).unwrap();
+    assert_eq!(get(&second), 1);
+
+    let old_second = second.addr();
+    crash(&first);
+    while old_second.is_alive() {
+        sleep(Duration::from_millis(1));
+    }
+    assert_eq!(get(&first), 0);
+    assert_eq!(get(&second), 0);
+
+    supervisor.stop().unwrap();
+}
+
+#[test]
+fn test_backoff() {
+    let supervisor = Supervisor::start(Policy {
+        backoff: Backoff::Fixed(Duration::from_millis(200)),
+        ..Policy::default()
+    });
+    let actor = start_supervised(&supervisor);
+    let crashed_at = Instant::now();
+    crash(&actor);
+    assert_eq!(get(&actor), 0);
+    assert!(crashed_at.elapsed() >= Duration::from_millis(200));
+    supervisor.stop().unwrap();
+}
+
+#[test]
+fn test_too_many_restarts() {
+    let supervisor = Supervisor::start(Policy {
+        max_restarts: 1,
+        window: Duration::from_secs(60),
+        ..Policy::default()
+    });
+    let actor = start_supervised(&supervisor);
+    crash(&actor);
+    assert_eq!(get(&actor), 0);
+    // Second panic within the window - the supervisor gives up
+    crash(&actor);
+    sleep(Duration::from_millis(100));
+    assert!(!actor.addr().is_alive());
+    assert_eq!(supervisor.stop(), Err(SupervisorError::TooManyRestarts));
+}
+
+#[test]
+fn test_normal_stop_is_not_restarted() {
+    let supervisor = Supervisor::start(Policy::default());
+    let actor = start_supervised(&supervisor);
+    actor.addr().ask(Input::Get).unwrap();
+    supervisor.stop().unwrap();
+    assert!(!actor.addr().is_alive());
+}
+
+#[cfg(target_os = "linux")]
+mod slow {
+    use movie::actor;
+
+    actor! {
+        SlowStopActor
+            public_visibility: true,
+            input:
+                Ping -> (),
+            on_message:
+                Ping(reply) => reply.send(()),
+            on_stop:
+                std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+}
+
+/// CPU time used by the whole test process so far.
+#[cfg(target_os = "linux")]
+fn cpu_time() -> Duration {
+    let stat = std::fs::read_to_string("/proc/self/stat").unwrap();
+    // Fields after the command name, starting with the state (3rd field)
+    let fields: Vec<&str> = stat.rsplit(") ").next().unwrap().split(' ').collect();
+    let ticks: u64 = fields[11].parse::<u64>().unwrap() + fields[12].parse::<u64>().unwrap();
+    // Clock ticks are 1/100s on Linux
+    Duration::from_millis(ticks * 10)
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_one_for_all_waits_without_spinning() {
+    let supervisor = Supervisor::start(Policy {
+        strategy: Strategy::OneForAll,
+        ..Policy::default()
+    });
+    let counter = start_supervised(&supervisor);
+    let slow = supervisor.supervise(|| slow::SlowStopActor::Actor {}.start());
+    slow.addr().ask(slow::SlowStopActor::Input::Ping).unwrap();
+
+    let old_slow = slow.addr();
+    let cpu_before = cpu_time();
+    crash(&counter);
+    // The supervisor waits for the slow actor to stop before restarting it
+    while old_slow.is_alive() {
+        sleep(Duration::from_millis(10));
+    }
+    for _ in 0..500 {
+        if slow.addr().ask(slow::SlowStopActor::Input::Ping).is_ok() {
+            break;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    let cpu_used = cpu_time() - cpu_before;
+    assert!(cpu_used < Duration::from_millis(250), "{:?}", cpu_used);
+    assert_eq!(get(&counter), 0);
+
+    supervisor.stop().unwrap();
+}
+
+mod panicky {
+    use movie::actor;
+
+    actor! {
+        PanicOnStopActor
+            public_visibility: true,
+            on_stop:
+                panic!("panicked while stopping");
+    }
+}
+
+#[test]
+fn test_stop_reports_panics() {
+    let supervisor = Supervisor::start(Policy::default());
+    let first = start_supervised(&supervisor);
+    supervisor.supervise(|| panicky::PanicOnStopActor::Actor {}.start());
+    let third = start_supervised(&supervisor);
+    assert_eq!(get(&first), 0);
+    assert_eq!(get(&third), 0);
+
+    assert_eq!(
+        supervisor.stop(),
+        Err(SupervisorError::PanickedWhileStopping(vec![1]))
+    );
+    // The other actors were stopped too
+    assert!(!first.addr().is_alive());
+    assert!(!third.addr().is_alive());
+}
@@ -0,0 +1,61 @@
+use movie::actor;
+
+actor! {
+    ClosureActor
+        data:
+            pub total: u64,
+        tick_interval: 1,
+        on_tick:
+            // `data:` and `input:` are closure parameters, not attributes
+            let add = |data: u64, input: u64| data + input;
+            self.total = add(self.total, add(0, 1));
+            if self.total == 3 {
+                ctx.stop();
+            }
+        returns: u64,
+        on_stop: self.total,
+}
+
+#[test]
+fn test_closure_parameters() {
+    let actor = ClosureActor::Actor { total: 0 }.start();
+    assert_eq!(actor.join().unwrap(), 3);
+}
+
+actor! {
+    ComparisonActor
+        input:
+            Add(u64),
+            Total -> u64,
+        data:
+            pub total: u64,
+            pub history: Vec<Option<u64>>,
+        on_message:
+            // `MAX < ` is a comparison, not generic arguments
+            Add(n) => if MAX < self.total + n { self.total = 0 } else { self.total += n },
+            Total(reply) => reply.send(self.total),
+        tick_interval: 1,
+        on_tick:
+            let last: Option<u64> = self.history.last().copied().flatten();
+            if last < Some(self.total) {
+                self.history.push(Some(self.total));
+            }
+        custom_code:
+            const MAX: u64 = 10;
+}
+
+#[test]
+fn test_comparison() {
+    use ComparisonActor::{Actor, HandleExt};
+
+    let actor = Actor {
+        total: 0,
+        history: vec![],
+    }
+    .start();
+    actor.add(6).unwrap();
+    assert_eq!(actor.total().unwrap(), 6);
+    actor.add(6).unwrap();
+    assert_eq!(actor.total().unwrap(), 0);
+    actor.stop().unwrap();
+}
@@ -0,0 +1,83 @@
+use movie::actor;
+
+actor! {
+    NamedActor
+        input:
+            Hang(std::sync::mpsc::Receiver<()>),
+            Panic,
+        data:
+            pub name: &'static str,
+            pub stopped_tx: std::sync::mpsc::Sender<&'static str>,
+        on_message:
+            Hang(rx) => { let _ = rx.recv(); },
+            Panic => panic!("named actor broke"),
+        on_stop:
+            self.stopped_tx.send(self.name).unwrap();
+}
+
+use movie::ActorSystem;
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+use NamedActor::{Actor, Input};
+
+fn start(name: &'static str, stopped_tx: &Sender<&'static str>) -> NamedActor::Handle {
+    Actor {
+        name,
+        stopped_tx: stopped_tx.clone(),
+    }
+    .start()
+}
+
+#[test]
+fn test_reverse_order() {
+    let (stopped_tx, stopped_rx) = channel();
+    let mut system = ActorSystem::new();
+    system.add("first", start("first", &stopped_tx));
+    system.add("second", start("second", &stopped_tx));
+    system.add("third", start("third", &stopped_tx));
+    assert_eq!(system.len(), 3);
+
+    let report = system.shutdown(Duration::from_secs(5));
+    assert!(report.is_clean());
+    assert_eq!(report.stopped, vec!["third", "second", "first"]);
+    let order: Vec<_> = stopped_rx.try_iter().collect();
+    assert_eq!(order, vec!["third", "second", "first"]);
+}
+
+#[test]
+fn test_report() {
+    let (stopped_tx, _stopped_rx) = channel();
+    let mut system = ActorSystem::new();
+    let stuck = system.add("stuck", start("stuck", &stopped_tx));
+    let broken = system.add("broken", start("broken", &stopped_tx));
+    system.add("fine", start("fine", &stopped_tx));
+
+    let (gate, rx) = channel();
+    stuck.send(Input::Hang(rx)).unwrap();
+    broken.send(Input::Panic).unwrap();
+
+    let report = system.shutdown(Duration::from_millis(200));
+    assert!(!report.is_clean());
+    assert_eq!(report.stopped, vec!["fine"]);
+    assert_eq!(report.panicked, vec!["broken"]);
+    assert_eq!(report.timed_out, vec!["stuck"]);
+    assert!(stuck.is_alive());
+    drop(gate);
+}
+
+#[test]
+fn test_stopped_after_deadline() {
+    let (stopped_tx, _stopped_rx) = channel();
+    let mut system = ActorSystem::new();
+    // Stopped last, once the deadline has passed
+    system.add("late", start("late", &stopped_tx));
+    let stuck = system.add("stuck", start("stuck", &stopped_tx));
+
+    let (gate, rx) = channel();
+    stuck.send(Input::Hang(rx)).unwrap();
+
+    let report = system.shutdown(Duration::from_millis(100));
+    assert_eq!(report.timed_out, vec!["stuck"]);
+    assert_eq!(report.stopped, vec!["late"]);
+    drop(gate);
+}
@@ -0,0 +1,125 @@
+use movie::actor;
+
+use std::time::Duration;
+
+actor! {
+    ParserActor
+        input:
+            Parse(String),
+            Flushed -> u64,
+            Quit,
+        data:
+            pub lines_parsed: u64,
+            pub buffer: Vec<String>,
+            pub flushes: u64,
+        tick_interval: 5,
+        timers:
+            flush(12) => {
+                self.buffer.clear();
+                self.flushes += 1;
+            },
+        on_message:
+            Parse(line) => self.buffer.push(line),
+            Flushed(reply) => reply.send(self.flushes),
+            Quit => ctx.stop(),
+        on_tick:
+            self.lines_parsed += 1;
+        returns: u64,
+        on_stop: self.lines_parsed,
+}
+
+fn parser() -> movie::TestActor<ParserActor::Actor> {
+    movie::TestActor::new(ParserActor::Actor {
+        lines_parsed: 0,
+        buffer: vec![],
+        flushes: 0,
+    })
+}
+
+#[test]
+fn test_ticks_and_timers() {
+    let mut actor = parser();
+    let start = actor.now();
+    actor.step_ticks(20);
+    assert_eq!(actor.ctx().tick_count(), 20);
+    assert_eq!(actor.actor().lines_parsed, 20);
+    assert_eq!(actor.now() - start, Duration::from_millis(100));
+    // Fired at 12ms, 24ms, ..., 96ms
+    assert_eq!(actor.actor().flushes, 8);
+
+    actor.advance(Duration::from_millis(100));
+    assert_eq!(actor.actor().lines_parsed, 40);
+    assert_eq!(actor.now() - start, Duration::from_millis(200));
+    assert_eq!(actor.stop(), 40);
+}
+
+#[test]
+fn test_deliver() {
+    use ParserActor::Input;
+
+    let mut actor = parser();
+    actor.send(Input::Parse("a".to_string())).unwrap();
+    actor.send(Input::Parse("b".to_string())).unwrap();
+    assert!(actor.actor().buffer.is_empty());
+    assert!(actor.deliver());
+    assert_eq!(actor.actor().buffer, ["a"]);
+    assert_eq!(actor.deliver_all(), 1);
+    assert_eq!(actor.actor().buffer, ["a", "b"]);
+    assert!(!actor.deliver());
+
+    // Messages wait while the clock moves
+    actor.send(Input::Parse("c".to_string())).unwrap();
+    actor.advance(Duration::from_millis(12));
+    assert!(actor.actor().buffer.is_empty());
+    assert_eq!(actor.ask(Input::Flushed), 1);
+    assert_eq!(actor.actor().buffer, ["c"]);
+
+    actor.send(Input::Quit).unwrap();
+    actor.send(Input::Parse("d".to_string())).unwrap();
+    assert!(actor.deliver());
+    assert!(actor.ctx().is_stopping());
+    assert!(!actor.deliver());
+    // Nothing runs once the actor is stopping
+    actor.step_ticks(5);
+    assert_eq!(actor.stop(), 2);
+}
+
+actor! {
+    GreeterActor
+        input:
+            Greeting -> String,
+        data:
+            pub name: Option<String>,
+            pub greeting: String,
+        on_init:
+            // Not a variable of the actor, it can still be run step by step
+            if let Some(name) = self.name.take() {
+                self.greeting = format!("Hello, {}!", name);
+            }
+        on_message:
+            Greeting(reply) => reply.send(self.greeting.clone()),
+}
+
+#[test]
+fn test_if_let_in_on_init() {
+    use movie::{Pump, Pumped};
+    use GreeterActor::{Actor, HandleExt};
+
+    let greeter = || Actor {
+        name: Some("world".to_string()),
+        greeting: String::new(),
+    };
+    let mut actor = movie::TestActor::new(greeter());
+    assert_eq!(actor.ask(GreeterActor::Input::Greeting), "Hello, world!");
+    actor.stop();
+
+    let mut actor = Pump::new(greeter());
+    let addr = actor.addr();
+    let asker = std::thread::spawn(move || addr.greeting().unwrap());
+    while !asker.is_finished() {
+        actor.pump();
+    }
+    assert_eq!(asker.join().unwrap(), "Hello, world!");
+    actor.request_stop();
+    assert!(matches!(actor.pump(), Pumped::Stopped(())));
+}
@@ -0,0 +1,71 @@
+use movie::actor;
+
+use std::thread;
+
+actor! {
+    CameraActor
+        input:
+            ThreadName -> Option<String>,
+        data:
+            pub id: u32,
+        instance_id: self.id,
+        stack_size: 65_536,
+        on_message:
+            ThreadName(reply) => reply.send(thread::current().name().map(str::to_string)),
+}
+
+actor! {
+    HugeStackActor
+        // Larger than the address space, the thread can't be spawned
+        stack_size: 4_611_686_018_427_387_904,
+}
+
+#[test]
+fn test_thread_name() {
+    use CameraActor::{Actor, HandleExt};
+
+    let actor = Actor { id: 3 }.start();
+    assert_eq!(
+        actor.thread_name().unwrap().as_deref(),
+        Some("CameraActor-3")
+    );
+    actor.stop().unwrap();
+
+    let actor = Actor { id: 4 }.try_start().unwrap();
+    assert_eq!(
+        actor.thread_name().unwrap().as_deref(),
+        Some("CameraActor-4")
+    );
+    actor.stop().unwrap();
+}
+
+#[test]
+fn test_try_start_failure() {
+    assert!(HugeStackActor::Actor {}.try_start().is_err());
+}
+
+mod attribute_form {
+    use movie::attr::actor;
+
+    #[actor(instance_id = self.port, stack_size = 65_536)]
+    pub struct ListenerActor {
+        pub port: u16,
+    }
+
+    #[actor]
+    impl ListenerActor {
+        pub fn thread_name(&self) -> Option<String> {
+            std::thread::current().name().map(str::to_string)
+        }
+    }
+
+    #[test]
+    fn test_thread_name() {
+        let actor = ListenerActor { port: 8080 }.try_start().unwrap();
+        assert_eq!(
+            actor.thread_name().unwrap().as_deref(),
+            Some("ListenerActor-8080")
+        );
+        actor.stop().unwrap();
+    }
+}
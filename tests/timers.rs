@@ -0,0 +1,174 @@
+use movie::actor;
+
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+
+actor! {
+    HeartbeatActor
+        input:
+            Pause,
+            Resume,
+            Cancel,
+            SetInterval(u64),
+            Status -> (bool, Option<Duration>),
+        data:
+            pub beats_tx: Sender<&'static str>,
+        tick_interval: 60_000,
+        timers:
+            beat(1) => self.beats_tx.send("beat").unwrap(),
+            idle(Duration::from_secs(60)) => self.beats_tx.send("idle").unwrap(),
+        on_message:
+            Pause => {
+                ctx.pause_timer("beat");
+            },
+            Resume => {
+                ctx.resume_timer("beat");
+            },
+            Cancel => {
+                ctx.cancel_timer("beat");
+            },
+            SetInterval(millis) => {
+                ctx.set_timer_interval("beat", Duration::from_millis(millis));
+            },
+            Status(reply) => reply.send((ctx.is_timer_running("beat"), ctx.timer_interval("beat"))),
+}
+
+#[test]
+fn test_timers() {
+    use HeartbeatActor::{Actor, HandleExt};
+
+    let (beats_tx, beats_rx) = channel();
+    let actor = Actor { beats_tx }.start();
+    // `beat` fires repeatedly, long before `idle` and the first tick
+    for _ in 0..3 {
+        assert_eq!(beats_rx.recv_timeout(Duration::from_secs(5)), Ok("beat"));
+    }
+
+    actor.pause().unwrap();
+    assert_eq!(
+        actor.status().unwrap(),
+        (false, Some(Duration::from_millis(1)))
+    );
+    beats_rx.try_iter().for_each(drop);
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(beats_rx.try_recv().is_err());
+
+    actor.resume().unwrap();
+    assert_eq!(beats_rx.recv_timeout(Duration::from_secs(5)), Ok("beat"));
+
+    actor.set_interval(60_000).unwrap();
+    assert_eq!(
+        actor.status().unwrap(),
+        (true, Some(Duration::from_secs(60)))
+    );
+    beats_rx.try_iter().for_each(drop);
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(beats_rx.try_recv().is_err());
+
+    actor.cancel().unwrap();
+    assert_eq!(actor.status().unwrap(), (false, None));
+    actor.stop().unwrap();
+}
+
+actor! {
+    OneShotActor
+        data:
+            pub fired_tx: Sender<u64>,
+            pub fired: u64,
+        tick_interval: 60_000,
+        on_init:
+            // Without this, the actor would stop after 20 minutes
+            ctx.set_tick_interval(Duration::from_millis(1));
+        timers:
+            once(1) => {
+                self.fired += 1;
+                ctx.cancel_timer("once");
+            },
+        on_tick:
+            if ctx.tick_count() == 20 {
+                ctx.stop();
+            }
+        on_stop:
+            self.fired_tx.send(self.fired).unwrap();
+}
+
+#[test]
+fn test_timer_changed_by_its_handler() {
+    use OneShotActor::Actor;
+
+    let (fired_tx, fired_rx) = channel();
+    let _actor = Actor { fired_tx, fired: 0 }.start();
+    assert_eq!(fired_rx.recv_timeout(Duration::from_secs(5)), Ok(1));
+}
+
+actor! {
+    SuffixedIntervalActor
+        input:
+            Intervals -> (Option<Duration>, Option<Duration>),
+        timers:
+            // Integer literals are milliseconds, whatever their suffix
+            short(1_000u32) => (),
+            long(0x10_000u64) => (),
+        on_message:
+            Intervals(reply) => reply.send((ctx.timer_interval("short"), ctx.timer_interval("long"))),
+}
+
+#[test]
+fn test_suffixed_intervals() {
+    use SuffixedIntervalActor::{Actor, HandleExt};
+
+    let actor = Actor {}.start();
+    assert_eq!(
+        actor.intervals().unwrap(),
+        (
+            Some(Duration::from_secs(1)),
+            Some(Duration::from_millis(65_536))
+        )
+    );
+    actor.stop().unwrap();
+}
+
+mod attribute_form {
+    use movie::attr::actor;
+    use movie::Context;
+
+    use std::sync::mpsc::{channel, Sender};
+    use std::time::Duration;
+
+    #[actor]
+    pub struct PollingActor {
+        pub polls_tx: Sender<&'static str>,
+    }
+
+    #[actor]
+    impl PollingActor {
+        fn on_init(&mut self, ctx: &mut Context<PollingActorInput>) {
+            ctx.start_timer("poll", Duration::from_millis(1));
+        }
+
+        fn on_timer(&mut self, ctx: &mut Context<PollingActorInput>, timer: &'static str) {
+            self.polls_tx.send(timer).unwrap();
+            ctx.cancel_timer(timer);
+            if timer == "poll" {
+                ctx.start_timer("last_poll", Duration::from_millis(1));
+            }
+        }
+
+        pub fn ping(&self) {}
+    }
+
+    #[test]
+    fn test_on_timer() {
+        let (polls_tx, polls_rx) = channel();
+        let actor = PollingActor { polls_tx }.start();
+        actor.ping().unwrap();
+        assert_eq!(polls_rx.recv_timeout(Duration::from_secs(5)), Ok("poll"));
+        assert_eq!(
+            polls_rx.recv_timeout(Duration::from_secs(5)),
+            Ok("last_poll")
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(polls_rx.try_recv().is_err());
+        actor.stop().unwrap();
+    }
+}
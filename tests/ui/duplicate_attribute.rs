@@ -0,0 +1,9 @@
+use movie::actor;
+
+actor! {
+    DuplicateActor
+        tick_interval: 5,
+        tick_interval: 10,
+}
+
+fn main() {}
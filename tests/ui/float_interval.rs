@@ -0,0 +1,9 @@
+use movie::actor;
+
+actor! {
+    FloatIntervalActor
+        timers:
+            report(1.5) => (),
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use movie::actor;
+
+actor! {
+    InvalidValueActor
+        tick_interval: fast,
+}
+
+fn main() {}
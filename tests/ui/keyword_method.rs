@@ -0,0 +1,11 @@
+use movie::actor;
+
+actor! {
+    KeywordMethodActor
+        input:
+            Super,
+        on_message:
+            Super => (),
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use movie::actor;
+
+actor! {
+    GuiActor
+        local_data:
+            labels: std::rc::Rc<Vec<String>> = Default::default(),
+}
+
+fn main() {
+    // The local data stays with the `Pump`, on this thread
+    let mut pump = movie::Pump::new(GuiActor::Actor {});
+    pump.pump();
+    std::thread::spawn(move || pump.pump());
+}
@@ -0,0 +1,12 @@
+use movie::actor;
+
+actor! {
+    GuiActor
+        local_data:
+            labels: std::rc::Rc<Vec<String>> = Default::default(),
+}
+
+fn main() {
+    let pool = movie::ThreadPool::new(2);
+    pool.start(GuiActor::Actor {}).stop().unwrap();
+}
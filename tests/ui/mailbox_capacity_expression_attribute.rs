@@ -0,0 +1,9 @@
+use movie::attr::actor;
+
+const CAPACITY: usize = 0;
+
+// Checked like `actor!`, which only takes literals
+#[actor(mailbox_capacity = CAPACITY)]
+pub struct ConstCapacityActor {}
+
+fn main() {}
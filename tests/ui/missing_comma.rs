@@ -0,0 +1,10 @@
+use movie::actor;
+
+actor! {
+    MissingCommaActor
+        data:
+            pub a: u32
+            pub b: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use movie::actor;
+
+actor! {
+    MisspelledActor
+        input: Ping,
+        on_mesage:
+            Ping => (),
+}
+
+fn main() {}
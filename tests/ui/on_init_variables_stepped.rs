@@ -0,0 +1,14 @@
+use movie::actor;
+
+actor! {
+    CounterActor
+        on_init:
+            let mut count = 0;
+        on_tick:
+            count += 1;
+}
+
+fn main() {
+    movie::Pump::new(CounterActor::Actor {});
+    movie::TestActor::new(CounterActor::Actor {});
+}
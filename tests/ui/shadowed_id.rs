@@ -0,0 +1,11 @@
+use movie::actor;
+
+actor! {
+    ShadowedIdActor
+        input:
+            Id(u32),
+        on_message:
+            _ => (),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use movie::actor;
+
+actor! {
+    ShadowedMethodActor
+        input:
+            Send(u32),
+        on_message:
+            Send(_) => (),
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use movie::actor;
+
+use std::sync::mpsc::Receiver;
+
+actor! {
+    DownloadActor
+        output:
+            Finished(usize),
+}
+
+fn main() {
+    let actor = DownloadActor::Actor {}.start();
+    // Only `DownloadActor::Output` can be received
+    let _events: Receiver<String> = actor.subscribe();
+}
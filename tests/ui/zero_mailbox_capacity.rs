@@ -0,0 +1,8 @@
+use movie::actor;
+
+actor! {
+    ZeroCapacityActor
+        mailbox_capacity: 0,
+}
+
+fn main() {}
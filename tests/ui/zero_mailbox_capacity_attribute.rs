@@ -0,0 +1,7 @@
+use movie::attr::actor;
+
+// Suffixed, but still 0
+#[actor(mailbox_capacity = 0usize)]
+pub struct ZeroCapacityActor {}
+
+fn main() {}